@@ -9,10 +9,14 @@ Usage:
 """
 
 import asyncio
+import base64
 import json
+import struct
+import sys
 import threading
 import webbrowser
-from typing import Optional
+from array import array
+from typing import Optional, Sequence, Union
 
 try:
     import websockets
@@ -21,7 +25,7 @@ except ImportError:
     raise ImportError("websockets package required. Install with: pip install websockets")
 
 __version__ = "0.1.0"
-__all__ = ["start", "load_model", "load_volume", "load_voxels", "show", "reset_zoom", "reset_rotation", "VibePlotConnection"]
+__all__ = ["start", "load_model", "load_obj", "load_stl", "load_ply", "load_points", "set_point_size", "load_polylines", "set_line_style", "set_axes", "set_axes_style", "set_gizmo", "load_gltf", "load_model_binary", "load_mesh", "encode_model_binary", "load_volume", "load_voxels", "show", "reset_zoom", "reset_rotation", "fit_to_view", "set_view", "set_projection", "add_object", "remove_object", "set_object_transform", "set_object_visible", "set_object_transparent", "set_transparency_method", "capture_png", "VibePlotConnection"]
 
 DEFAULT_PORT = 9753
 DEFAULT_HOST = "0.0.0.0"
@@ -40,6 +44,11 @@ class VibePlotConnection:
         self._connected = threading.Event()
         self._ready = threading.Event()
         self._started = threading.Event()
+        # Ids for add_object; 0 is the model put in place by the load_* calls
+        self._next_object_id = 1
+        # Screenshot requests awaiting the browser's reply: id -> [Event, reply]
+        self._captures = {}
+        self._next_capture_id = 1
 
     async def _handle_client(self, websocket):
         """Handle incoming browser connection."""
@@ -52,8 +61,17 @@ class VibePlotConnection:
                     if data.get("type") == "ready":
                         self._ready.set()
                         print("vibeplot: Browser connected")
+                    elif data.get("type") == "capture":
+                        pending = self._captures.get(data.get("id"))
+                        if pending:
+                            pending[1] = data
+                            pending[0].set()
                     elif data.get("type") == "ack" and not data.get("success"):
-                        print(f"vibeplot error: {data.get('error')}")
+                        if "line" in data:
+                            # Model parse errors carry their location in the message
+                            print(f"vibeplot error ({data.get('kind')}): {data.get('error')}")
+                        else:
+                            print(f"vibeplot error: {data.get('error')}")
                 except json.JSONDecodeError:
                     pass
         except websockets.exceptions.ConnectionClosed:
@@ -70,6 +88,8 @@ class VibePlotConnection:
             self._handle_client,
             self.host,
             self.port,
+            # Screenshots of large or high-DPI canvases can pass the 1 MiB default
+            max_size=None,
         )
         self._started.set()
         print(f"vibeplot: Server started at ws://{self.host}:{self.port}")
@@ -122,6 +142,92 @@ class VibePlotConnection:
             "data": model_text
         })
 
+    def load_obj(self, obj_text: str, mtl_text: Optional[str] = None):
+        """Send a Wavefront OBJ model (and optional MTL materials) to browser."""
+        self._send({
+            "type": "load_obj",
+            "data": obj_text,
+            "mtl": mtl_text,
+        })
+
+    def load_stl(self, stl_bytes: bytes, color: Optional[Sequence[float]] = None):
+        """Send raw binary or ASCII STL bytes to browser."""
+        self._send({
+            "type": "load_stl",
+            "data": base64.b64encode(stl_bytes).decode("ascii"),
+            "color": list(color) if color is not None else None,
+        })
+
+    def load_ply(self, ply_bytes: bytes, color_by: Optional[str] = None):
+        """Send raw ASCII or binary PLY bytes to browser."""
+        self._send({
+            "type": "load_ply",
+            "data": base64.b64encode(ply_bytes).decode("ascii"),
+            "color_by": color_by,
+        })
+
+    def load_points(self, positions: bytes, colors: Optional[bytes] = None, sizes: Optional[bytes] = None):
+        """Send a point cloud as packed little-endian float32 arrays to browser."""
+        self._send({
+            "type": "load_points",
+            "positions": base64.b64encode(positions).decode("ascii"),
+            "colors": base64.b64encode(colors).decode("ascii") if colors is not None else None,
+            "sizes": base64.b64encode(sizes).decode("ascii") if sizes is not None else None,
+        })
+
+    def set_point_size(self, size: float, mode: str):
+        """Set the point cloud point size, in "screen" pixels or "world" units."""
+        self._send({"type": "set_point_size", "size": size, "mode": mode})
+
+    def load_polylines(self, positions: bytes, colors: Optional[bytes], lengths: Sequence[int], width: float):
+        """Send polylines as packed little-endian float32 points, split by lengths, to browser."""
+        self._send({
+            "type": "load_polylines",
+            "positions": base64.b64encode(positions).decode("ascii"),
+            "colors": base64.b64encode(colors).decode("ascii") if colors is not None else None,
+            "lengths": lengths,
+            "width": width,
+        })
+
+    def set_line_style(self, join: str, cap: str):
+        """Set how polylines are joined and capped."""
+        self._send({"type": "set_line_style", "join": join, "cap": cap})
+
+    def set_axes(self, axes: bool, grid: str, ticks: bool, labels: bool):
+        """Show or hide the axes, grid, tick marks and tick labels."""
+        self._send({"type": "set_axes", "axes": axes, "grid": grid, "ticks": ticks, "labels": labels})
+
+    def set_axes_style(self, axis_width: float, grid_width: float, label_size: float,
+                       axis_colors: Optional[list], grid_color: Optional[list], label_color: Optional[list]):
+        """Set line widths, label size and colors of the axes and grid."""
+        self._send({
+            "type": "set_axes_style",
+            "axis_width": axis_width,
+            "grid_width": grid_width,
+            "label_size": label_size,
+            "axis_colors": axis_colors,
+            "grid_color": grid_color,
+            "label_color": label_color,
+        })
+
+    def set_gizmo_visible(self, visible: bool):
+        """Show or hide the orientation gizmo."""
+        self._send({"type": "set_gizmo_visible", "visible": visible})
+
+    def load_gltf(self, gltf_bytes: bytes):
+        """Send a .gltf (embedded buffers) or .glb file's bytes to browser."""
+        self._send({
+            "type": "load_gltf",
+            "data": base64.b64encode(gltf_bytes).decode("ascii"),
+        })
+
+    def load_model_binary(self, model_bytes: bytes):
+        """Send a model in the compact binary format to browser."""
+        self._send({
+            "type": "load_model_binary",
+            "data": base64.b64encode(model_bytes).decode("ascii"),
+        })
+
     def reset_zoom(self):
         """Reset zoom to default."""
         self._send({"type": "reset_zoom"})
@@ -130,6 +236,70 @@ class VibePlotConnection:
         """Reset rotation to default."""
         self._send({"type": "reset_rotation"})
 
+    def set_view(self, view: str):
+        """Animate to a standard view."""
+        self._send({"type": "set_view", "view": view})
+
+    def fit_to_view(self):
+        """Frame the whole model."""
+        self._send({"type": "fit_to_view"})
+
+    def set_projection(self, mode: str):
+        """Switch between "perspective" and "orthographic" projection."""
+        self._send({"type": "set_projection", "mode": mode})
+
+    def set_transparency_method(self, method: str):
+        """Switch between "sorted" and "weighted" transparency."""
+        self._send({"type": "set_transparency_method", "method": method})
+
+    def add_object(self, model_text: str) -> int:
+        """Add a model to the scene next to what is shown, returning its id."""
+        object_id = self._next_object_id
+        self._next_object_id += 1
+        self._send({"type": "add_object", "data": model_text, "id": object_id})
+        return object_id
+
+    def remove_object(self, object_id: int):
+        """Remove an object from the scene."""
+        self._send({"type": "remove_object", "id": object_id})
+
+    def set_object_transform(self, object_id: int, matrix: Sequence[float]):
+        """Set an object's transform from 16 column-major numbers."""
+        self._send({"type": "set_object_transform", "id": object_id, "matrix": list(matrix)})
+
+    def set_object_visible(self, object_id: int, visible: bool):
+        """Show or hide an object."""
+        self._send({"type": "set_object_visible", "id": object_id, "visible": visible})
+
+    def set_object_transparent(self, object_id: int, transparent: bool):
+        """Draw an object with the transparent pipeline."""
+        self._send({"type": "set_object_transparent", "id": object_id, "transparent": transparent})
+
+    def capture_png(self, width: int, height: int, transparent_background: bool = False,
+                    timeout: float = 60.0) -> bytes:
+        """Render the current view at width x height pixels and return PNG bytes."""
+        capture_id = self._next_capture_id
+        self._next_capture_id += 1
+        pending = [threading.Event(), None]
+        self._captures[capture_id] = pending
+        try:
+            self._send({
+                "type": "capture_png",
+                "id": capture_id,
+                "width": width,
+                "height": height,
+                "transparent_background": transparent_background,
+            })
+            if not pending[0].wait(timeout=timeout):
+                raise TimeoutError("Timed out waiting for the browser to capture the view")
+        finally:
+            del self._captures[capture_id]
+
+        reply = pending[1]
+        if not reply.get("success"):
+            raise RuntimeError(f"vibeplot capture failed: {reply.get('error')}")
+        return base64.b64decode(reply["data"])
+
     def close(self):
         """Shutdown the server."""
         if self._server:
@@ -190,6 +360,334 @@ def load_model(model_text: str):
     _connection.load_model(model_text)
 
 
+def load_obj(obj_text: str, mtl_text: Optional[str] = None):
+    """Send a Wavefront OBJ model, optionally with its MTL file contents, to connected browser."""
+    if not _connection:
+        raise RuntimeError("Not started. Call vibeplot.start() first.")
+    _connection.load_obj(obj_text, mtl_text)
+
+
+def load_stl(stl: Union[bytes, str], color: Optional[Sequence[float]] = None):
+    """
+    Send an STL model to connected browser.
+
+    Args:
+        stl:   Raw STL bytes (binary or ASCII), or a path to an ``.stl`` file.
+        color: Optional ``(r, g, b)`` or ``(r, g, b, a)`` in 0–1 applied to every facet.
+    """
+    if not _connection:
+        raise RuntimeError("Not started. Call vibeplot.start() first.")
+    if isinstance(stl, str):
+        with open(stl, "rb") as f:
+            stl = f.read()
+    _connection.load_stl(stl, color)
+
+
+def load_ply(ply: Union[bytes, str], color_by: Optional[str] = None):
+    """
+    Send a PLY mesh to connected browser. Files without faces are shown as
+    point clouds.
+
+    Args:
+        ply:      Raw PLY bytes (ASCII or binary), or a path to a ``.ply`` file.
+        color_by: Optional name of a scalar vertex property (e.g. ``"intensity"``)
+                  to color vertices by, using a viridis colormap.
+    """
+    if not _connection:
+        raise RuntimeError("Not started. Call vibeplot.start() first.")
+    if isinstance(ply, str):
+        with open(ply, "rb") as f:
+            ply = f.read()
+    _connection.load_ply(ply, color_by)
+
+
+def _float32_bytes(values) -> bytes:
+    """Flatten a sequence of numbers or of rows of numbers into little-endian float32 bytes."""
+    data = array("f")
+    for value in values:
+        try:
+            data.extend(float(v) for v in value)
+        except TypeError:
+            data.append(float(value))
+    if sys.byteorder != "little":
+        data.byteswap()
+    return data.tobytes()
+
+
+def load_points(positions, colors=None, sizes=None):
+    """
+    Send a point cloud to connected browser, replacing the scene. Points are
+    drawn as round, shaded splats; picking one reports its index.
+
+    Args:
+        positions: Sequence of ``(x, y, z)`` points.
+        colors:    Optional per-point ``(r, g, b)`` or ``(r, g, b, a)`` in 0–1.
+        sizes:     Optional per-point factor multiplying the point size (see
+                   ``set_point_size``).
+
+    Accepts lists or numpy arrays.
+    """
+    if not _connection:
+        raise RuntimeError("Not started. Call vibeplot.start() first.")
+    position_data = _float32_bytes(positions)
+    count = len(position_data) // 12
+    if len(position_data) % 12:
+        raise ValueError("positions must be (x, y, z) triples")
+    color_data = _float32_bytes(colors) if colors is not None else None
+    if color_data is not None and len(color_data) not in (count * 12, count * 16):
+        raise ValueError(f"colors must have 3 or 4 components for each of the {count} points")
+    size_data = _float32_bytes(sizes) if sizes is not None else None
+    if size_data is not None and len(size_data) != count * 4:
+        raise ValueError(f"sizes must have one value for each of the {count} points")
+    _connection.load_points(position_data, color_data, size_data)
+
+
+def set_point_size(size: float, mode: str = "screen"):
+    """
+    Set the diameter of point cloud points in connected browser.
+
+    Args:
+        size: Diameter, multiplied by each point's size factor.
+        mode: ``"screen"`` for CSS pixels (points keep their size as the view
+              zooms) or ``"world"`` for model units.
+    """
+    if mode not in ("screen", "world"):
+        raise ValueError(f"Unknown point size mode: {mode!r}")
+    if not size > 0:
+        raise ValueError("size must be positive")
+    if not _connection:
+        raise RuntimeError("Not started. Call vibeplot.start() first.")
+    _connection.set_point_size(size, mode)
+
+
+def load_polylines(polylines, colors=None, width: float = 2.0):
+    """
+    Send polylines to connected browser, replacing the scene. A polyline that
+    ends where it starts is closed; picking one reports its index.
+
+    Args:
+        polylines: Sequence of polylines, each a sequence of at least two
+                   ``(x, y, z)`` points.
+        colors:    Optional color for each polyline: one ``(r, g, b)`` or
+                   ``(r, g, b, a)`` in 0–1 for the whole line, or one per point,
+                   blended along the segments.
+        width:     Line width in CSS pixels.
+
+    Accepts lists or numpy arrays.
+    """
+    if not _connection:
+        raise RuntimeError("Not started. Call vibeplot.start() first.")
+    if not width > 0:
+        raise ValueError("width must be positive")
+    position_data = bytearray()
+    lengths = []
+    for line in polylines:
+        data = _float32_bytes(line)
+        if len(data) % 12:
+            raise ValueError("polyline points must be (x, y, z) triples")
+        if len(data) < 24:
+            raise ValueError("each polyline needs at least 2 points")
+        position_data += data
+        lengths.append(len(data) // 12)
+    color_data = None
+    if colors is not None:
+        if len(colors) != len(lengths):
+            raise ValueError(f"colors must have one entry for each of the {len(lengths)} polylines")
+        color_data = bytearray()
+        for line_colors, count in zip(colors, lengths):
+            line_colors = list(line_colors)
+            if line_colors and not hasattr(line_colors[0], "__len__"):
+                # One color for the whole line
+                line_colors = [line_colors] * count
+            if len(line_colors) != count or any(len(c) not in (3, 4) for c in line_colors):
+                raise ValueError(f"colors must be one color or one per point for a polyline of {count} points")
+            # Pad rgb to rgba so lines mixing both pack the same way
+            color_data += _float32_bytes(list(c) + [1.0] * (4 - len(c)) for c in line_colors)
+        color_data = bytes(color_data)
+    _connection.load_polylines(bytes(position_data), color_data, lengths, float(width))
+
+
+def set_line_style(join: str = "round", cap: str = "round"):
+    """
+    Set how polylines are drawn in connected browser.
+
+    Args:
+        join: Where segments meet: ``"round"``, ``"miter"`` or ``"bevel"``.
+        cap:  At open ends: ``"butt"``, ``"round"`` or ``"square"``.
+    """
+    if join not in ("round", "miter", "bevel"):
+        raise ValueError(f"Unknown line join: {join!r}")
+    if cap not in ("butt", "round", "square"):
+        raise ValueError(f"Unknown line cap: {cap!r}")
+    if not _connection:
+        raise RuntimeError("Not started. Call vibeplot.start() first.")
+    _connection.set_line_style(join, cap)
+
+
+def set_axes(axes: bool = True, grid: str = "ground", ticks: bool = True, labels: bool = True):
+    """
+    Show or hide plot axes around the scene in connected browser. Ticks fall
+    on round values spanning the scene's bounds and follow it as it changes.
+
+    Args:
+        axes:   Draw the X, Y and Z axis lines and their names.
+        grid:   ``"none"``, ``"ground"`` for a grid under the scene, or
+                ``"box"`` for grids on the three back planes.
+        ticks:  Draw tick marks along the axes.
+        labels: Label the ticks with their values.
+    """
+    if grid not in ("none", "ground", "box"):
+        raise ValueError(f"Unknown grid: {grid!r}")
+    if not _connection:
+        raise RuntimeError("Not started. Call vibeplot.start() first.")
+    _connection.set_axes(bool(axes), grid, bool(ticks), bool(labels))
+
+
+def set_axes_style(axis_width: float = 2.0, grid_width: float = 1.0, label_size: float = 12.0,
+                   axis_colors=None, grid_color=None, label_color=None):
+    """
+    Style the axes and grid in connected browser.
+
+    Args:
+        axis_width:  Axis and tick line width in CSS pixels.
+        grid_width:  Grid line width in CSS pixels.
+        label_size:  Tick label height in CSS pixels.
+        axis_colors: Optional three ``(r, g, b)`` or ``(r, g, b, a)`` colors in
+                     0–1 for the X, Y and Z axes.
+        grid_color:  Optional color of the grid lines.
+        label_color: Optional color of the tick labels.
+
+    Colors left as ``None`` keep their defaults.
+    """
+    for name, value in (("axis_width", axis_width), ("grid_width", grid_width), ("label_size", label_size)):
+        if not value > 0:
+            raise ValueError(f"{name} must be positive")
+
+    def color_values(color, name):
+        if color is None:
+            return None
+        color = [float(c) for c in color]
+        if len(color) not in (3, 4):
+            raise ValueError(f"{name} must be an (r, g, b) or (r, g, b, a) color")
+        return color + [1.0] * (4 - len(color))
+
+    if axis_colors is not None:
+        if len(axis_colors) != 3:
+            raise ValueError("axis_colors must have one color for each of the X, Y and Z axes")
+        axis_colors = [value for color in axis_colors for value in color_values(color, "axis_colors")]
+    grid_color = color_values(grid_color, "grid_color")
+    label_color = color_values(label_color, "label_color")
+    if not _connection:
+        raise RuntimeError("Not started. Call vibeplot.start() first.")
+    _connection.set_axes_style(float(axis_width), float(grid_width), float(label_size),
+                               axis_colors, grid_color, label_color)
+
+
+def set_gizmo(visible: bool = True):
+    """
+    Show or hide the orientation gizmo in the bottom-right corner of connected
+    browser: an X, Y, Z triad that turns with the model. Clicking one of its
+    axes turns the view to look down that axis.
+
+    Args:
+        visible: Whether the gizmo is shown. It is shown by default.
+    """
+    if not _connection:
+        raise RuntimeError("Not started. Call vibeplot.start() first.")
+    _connection.set_gizmo_visible(bool(visible))
+
+
+def load_gltf(gltf: Union[bytes, str]):
+    """
+    Send a glTF 2.0 scene to connected browser.
+
+    Args:
+        gltf: Raw ``.glb`` / ``.gltf`` bytes, or a path to such a file. Buffers
+              must be embedded (GLB binary chunk or base64 data URIs).
+    """
+    if not _connection:
+        raise RuntimeError("Not started. Call vibeplot.start() first.")
+    if isinstance(gltf, str):
+        with open(gltf, "rb") as f:
+            gltf = f.read()
+    _connection.load_gltf(gltf)
+
+
+# Binary model format: see parse_model_binary in src/model.rs
+BINARY_MODEL_MAGIC = b"VPLB"
+BINARY_MODEL_VERSION = 1
+BINARY_FLAG_GENERATE_NORMALS = 1 << 0
+BINARY_FLAG_FACE_IDS = 1 << 1
+DEFAULT_COLOR = (0.8, 0.8, 0.8, 1.0)
+
+
+def encode_model_binary(positions, faces, normals=None, colors=None, face_ids=None) -> bytes:
+    """
+    Pack a triangle mesh into vibeplot's little-endian binary model format.
+
+    Args:
+        positions: Sequence of ``(x, y, z)`` vertex positions.
+        faces:     Sequence of ``(i0, i1, i2)`` vertex indices, counter-clockwise.
+        normals:   Optional per-vertex ``(nx, ny, nz)``; smooth normals are
+                   generated in the browser when omitted.
+        colors:    Optional per-vertex ``(r, g, b)`` or ``(r, g, b, a)`` in 0–1.
+        face_ids:  Optional face id per triangle, so several triangles pick as one face.
+
+    Accepts lists or numpy arrays.
+    """
+    flags = 0
+    if normals is None:
+        flags |= BINARY_FLAG_GENERATE_NORMALS
+    if face_ids is not None:
+        flags |= BINARY_FLAG_FACE_IDS
+
+    vertex_data = array("f")
+    for i, pos in enumerate(positions):
+        nrm = normals[i] if normals is not None else (0.0, 0.0, 0.0)
+        col = colors[i] if colors is not None else DEFAULT_COLOR
+        alpha = col[3] if len(col) > 3 else 1.0
+        vertex_data.extend((pos[0], pos[1], pos[2], nrm[0], nrm[1], nrm[2], col[0], col[1], col[2], alpha))
+    index_data = array("I")
+    for f in faces:
+        index_data.extend((int(f[0]), int(f[1]), int(f[2])))
+    if face_ids is not None:
+        index_data.extend(int(i) for i in face_ids)
+
+    if sys.byteorder != "little":
+        vertex_data.byteswap()
+        index_data.byteswap()
+
+    header = struct.pack(
+        "<4sHHII", BINARY_MODEL_MAGIC, BINARY_MODEL_VERSION, flags, len(vertex_data) // 10, len(faces)
+    )
+    return header + vertex_data.tobytes() + index_data.tobytes()
+
+
+def load_model_binary(model: Union[bytes, str]):
+    """
+    Send a model in the compact binary format to connected browser.
+
+    Args:
+        model: Bytes from :func:`encode_model_binary`, or a path to a ``.vpb`` file.
+    """
+    if not _connection:
+        raise RuntimeError("Not started. Call vibeplot.start() first.")
+    if isinstance(model, str):
+        with open(model, "rb") as f:
+            model = f.read()
+    _connection.load_model_binary(model)
+
+
+def load_mesh(positions, faces, normals=None, colors=None, face_ids=None):
+    """
+    Send a triangle mesh to connected browser using the binary model format.
+
+    Much smaller and faster to parse than :func:`load_model` text for large
+    meshes. Arguments are as for :func:`encode_model_binary`.
+    """
+    load_model_binary(encode_model_binary(positions, faces, normals, colors, face_ids))
+
+
 def reset_zoom():
     """Reset zoom in connected browser."""
     if not _connection:
@@ -204,6 +702,160 @@ def reset_rotation():
     _connection.reset_rotation()
 
 
+VIEW_PRESETS = ("front", "back", "left", "right", "top", "bottom", "isometric")
+
+
+def set_view(view: str):
+    """
+    Animate the camera in connected browser to a standard view.
+
+    Args:
+        view: One of ``"front"``, ``"back"``, ``"left"``, ``"right"``, ``"top"``,
+              ``"bottom"`` or ``"isometric"``.
+    """
+    if view not in VIEW_PRESETS:
+        raise ValueError(f"Unknown view: {view!r}")
+    if not _connection:
+        raise RuntimeError("Not started. Call vibeplot.start() first.")
+    _connection.set_view(view)
+
+
+def fit_to_view():
+    """Center the model and move the camera so it fills the view in connected browser."""
+    if not _connection:
+        raise RuntimeError("Not started. Call vibeplot.start() first.")
+    _connection.fit_to_view()
+
+
+def set_projection(mode: str):
+    """
+    Set the projection in connected browser.
+
+    Args:
+        mode: ``"perspective"`` or ``"orthographic"`` (no foreshortening, for
+              comparing dimensions).
+    """
+    if mode not in ("perspective", "orthographic"):
+        raise ValueError(f"Unknown projection: {mode!r}")
+    if not _connection:
+        raise RuntimeError("Not started. Call vibeplot.start() first.")
+    _connection.set_projection(mode)
+
+
+def set_transparency_method(method: str):
+    """
+    Choose how transparent objects are blended in connected browser.
+
+    Args:
+        method: ``"sorted"`` (alpha blending, re-sorted as the view rotates) or
+                ``"weighted"`` (weighted blended order-independent
+                transparency, for nested or intersecting transparent surfaces).
+    """
+    if method not in ("sorted", "weighted"):
+        raise ValueError(f"Unknown transparency method: {method!r}")
+    if not _connection:
+        raise RuntimeError("Not started. Call vibeplot.start() first.")
+    _connection.set_transparency_method(method)
+
+
+def add_object(model_text: str) -> int:
+    """
+    Add a model to the scene in connected browser, alongside the loaded model
+    and any objects added before. Loading a model with a ``load_*`` function
+    replaces the whole scene.
+
+    Args:
+        model_text: Model in vibeplot's text format.
+
+    Returns:
+        The object's id, for ``remove_object`` and the ``set_object_*`` calls.
+        The model loaded by ``load_*`` has id 0.
+    """
+    if not _connection:
+        raise RuntimeError("Not started. Call vibeplot.start() first.")
+    return _connection.add_object(model_text)
+
+
+def remove_object(object_id: int):
+    """Remove an object from the scene in connected browser."""
+    if not _connection:
+        raise RuntimeError("Not started. Call vibeplot.start() first.")
+    _connection.remove_object(object_id)
+
+
+def set_object_transform(object_id: int, matrix):
+    """
+    Place an object in the scene in connected browser.
+
+    Args:
+        object_id: Id returned by ``add_object`` (0 for the loaded model).
+        matrix: 4x4 transform as nested rows (e.g. a numpy array, so
+                ``matrix[0][3]`` is the x translation), or 16 numbers in
+                column-major order.
+    """
+    if hasattr(matrix, "tolist"):
+        matrix = matrix.tolist()
+    matrix = list(matrix)
+    if len(matrix) == 4:
+        if any(len(row) != 4 for row in matrix):
+            raise ValueError("matrix must be 4x4")
+        matrix = [float(matrix[row][col]) for col in range(4) for row in range(4)]
+    elif len(matrix) == 16:
+        matrix = [float(v) for v in matrix]
+    else:
+        raise ValueError("matrix must be 4x4 or have 16 elements")
+    if not _connection:
+        raise RuntimeError("Not started. Call vibeplot.start() first.")
+    _connection.set_object_transform(object_id, matrix)
+
+
+def set_object_visible(object_id: int, visible: bool = True):
+    """Show or hide an object in connected browser. Hidden objects can't be picked."""
+    if not _connection:
+        raise RuntimeError("Not started. Call vibeplot.start() first.")
+    _connection.set_object_visible(object_id, bool(visible))
+
+
+def set_object_transparent(object_id: int, transparent: bool = True):
+    """Draw an object in connected browser with depth writes off, after the opaque objects."""
+    if not _connection:
+        raise RuntimeError("Not started. Call vibeplot.start() first.")
+    _connection.set_object_transparent(object_id, bool(transparent))
+
+
+def capture_png(path: Optional[str] = None, width: int = 1920, height: int = 1080,
+                transparent_background: bool = False) -> bytes:
+    """
+    Render the current view in connected browser offscreen and return it as PNG.
+
+    The image is rendered at the requested size whatever the size of the
+    browser window, for figures at print resolution.
+
+    Args:
+        path: If given, the PNG is also written to this file.
+        width: Image width in pixels.
+        height: Image height in pixels.
+        transparent_background: Leave the background transparent instead of
+                                the viewer's background color.
+
+    Returns:
+        The PNG file's bytes.
+
+    Example:
+        vibeplot.capture_png("figure.png", width=3840, height=2160)
+    """
+    width, height = int(width), int(height)
+    if width < 1 or height < 1:
+        raise ValueError("width and height must be at least 1")
+    if not _connection:
+        raise RuntimeError("Not started. Call vibeplot.start() first.")
+    png = _connection.capture_png(width, height, bool(transparent_background))
+    if path is not None:
+        with open(path, "wb") as f:
+            f.write(png)
+    return png
+
+
 def load_volume(volume, level: float = 0.0):
     """
     Visualize a 3D scalar field by extracting and rendering its isosurface.
@@ -246,22 +898,8 @@ def load_volume(volume, level: float = 0.0):
             f"Volume range: [{volume.min():.3f}, {volume.max():.3f}]"
         )
 
-    lines = ["# Volume isosurface"]
-    for pos, nrm in zip(positions, normals):
-        r = (nrm[0] + 1.0) / 2.0
-        g = (nrm[1] + 1.0) / 2.0
-        b = (nrm[2] + 1.0) / 2.0
-        lines.append(
-            f"vertex {pos[0]:.4f} {pos[1]:.4f} {pos[2]:.4f} "
-            f"{nrm[0]:.4f} {nrm[1]:.4f} {nrm[2]:.4f} "
-            f"{r:.3f} {g:.3f} {b:.3f}"
-        )
-    for f in faces:
-        lines.append(f"face {f[0]} {f[1]} {f[2]}")
-
-    if not _connection:
-        raise RuntimeError("Not started. Call vibeplot.start() first.")
-    _connection.load_model("\n".join(lines))
+    colors = [((n[0] + 1.0) / 2.0, (n[1] + 1.0) / 2.0, (n[2] + 1.0) / 2.0) for n in normals]
+    load_mesh(positions, faces, normals, colors)
 
 
 def load_voxels(volume, colormap: str = "plasma", threshold: float = 0.05,
@@ -271,9 +909,9 @@ def load_voxels(volume, colormap: str = "plasma", threshold: float = 0.05,
 
     Each grid cell is rendered as a small cube whose color and opacity are
     determined by its normalized scalar value.  Voxels below ``threshold``
-    (in normalized units) are skipped entirely.  The remaining voxels are
-    sorted back-to-front by distance from the volume centre so that alpha
-    blending composites correctly when viewed from the default camera angle.
+    (in normalized units) are skipped entirely.  The viewer re-sorts the
+    cubes back-to-front whenever the view rotates, so alpha blending
+    composites correctly from every angle.
 
     Args:
         volume:     3-D numpy array of shape (nx, ny, nz).
@@ -282,8 +920,8 @@ def load_voxels(volume, colormap: str = "plasma", threshold: float = 0.05,
         threshold:  Minimum normalised value [0–1] to include (default 0.05).
         alpha_scale: Overall opacity multiplier – reduce below 1.0 for more
                     transparent voxels (default 1.0).
-        max_voxels: Hard cap on the number of rendered voxels to stay within
-                    the GPU vertex-buffer limit (default 2000).
+        max_voxels: Hard cap on the number of rendered voxels to keep the
+                    model text and render cost manageable (default 2000).
 
     Example::
 
@@ -337,7 +975,7 @@ def load_voxels(volume, colormap: str = "plasma", threshold: float = 0.05,
     _cmaps = {"plasma": _plasma, "viridis": _viridis, "hot": _hot, "cool": _cool}
     cmap_fn = _cmaps.get(colormap, _plasma)
 
-    # ── Collect active voxels, sort back-to-front ─────────────────────────────
+    # ── Collect active voxels, farthest from the centre first ────────────────
     sx, sy, sz = 1.0 / nx, 1.0 / ny, 1.0 / nz
     hx, hy, hz = sx * 0.48, sy * 0.48, sz * 0.48
 
@@ -353,7 +991,8 @@ def load_voxels(volume, colormap: str = "plasma", threshold: float = 0.05,
                 cz = (iz + 0.5) * sz - 0.5
                 voxels.append((cx * cx + cy * cy + cz * cz, t, cx, cy, cz))
 
-    # Farthest first → correct back-to-front alpha blending
+    # Order decides which voxels are kept past max_voxels; blending order is
+    # handled by the viewer
     voxels.sort(key=lambda v: -v[0])
 
     # ── Face definitions for a voxel cube ────────────────────────────────────
@@ -372,7 +1011,7 @@ def load_voxels(volume, colormap: str = "plasma", threshold: float = 0.05,
     vc = 0
 
     for count, (_, t, cx, cy, cz) in enumerate(voxels):
-        if count >= max_voxels or vc + 24 > 64000:
+        if count >= max_voxels:
             break
         alpha = min(t * alpha_scale, 1.0)
         r, g, b = cmap_fn(t)
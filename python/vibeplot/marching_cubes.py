@@ -8,8 +8,9 @@ Public API:
     march(volume, level=0.0) -> (positions, normals, faces)
 """
 
-# Maximum triangles before we refuse (u16 index limit = 65535 / 3 = 21845)
-MAX_TRIANGLES = 21000
+# Maximum triangles before we refuse. The viewer switches to u32 indices for
+# large meshes, so this only bounds model text size and extraction time.
+MAX_TRIANGLES = 2_000_000
 
 # ---------------------------------------------------------------------------
 # Lookup tables (Lorensen & Cline 1987, public domain)
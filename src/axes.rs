@@ -0,0 +1,278 @@
+//! Plot axes: "nice number" ticks, and the lines and labels of the axes,
+//! tick marks and grid around the scene's bounds.
+
+use crate::glyphs::{layout_text, text_width};
+use crate::math::{project_point, Mat4};
+use crate::model::{Bounds, Polyline};
+use crate::state::{AxesSettings, AxesStyle, GridMode};
+use crate::vertex::{GlyphInstance, LineSegment};
+
+/// Ticks aimed for along each axis; the nice spacing gives between about
+/// half and twice as many.
+const TARGET_TICKS: usize = 5;
+/// Tick mark length, as a fraction of the longest axis.
+const TICK_LENGTH: f32 = 0.025;
+/// How far labels sit from their axis, in tick lengths.
+const LABEL_DISTANCE: f32 = 3.0;
+/// Least clear space between neighboring tick labels, in label heights.
+const LABEL_GAP: f32 = 0.5;
+/// Shortest axis, as a fraction of the scene's largest coordinate, so its
+/// ends stay apart at f32 precision however far the scene is from the origin.
+const MIN_RELATIVE_EXTENT: f32 = 1e-3;
+/// Most decimals a tick label gets, well past f32 precision.
+const MAX_TICK_DECIMALS: f32 = 9.0;
+/// Line id of axes and gizmo geometry, which is never picked or highlighted.
+pub const AXES_LINE_ID: u32 = u32::MAX;
+
+/// A "nice" number close to `x`: 1, 2 or 5 times a power of ten, rounded to
+/// the nearest if `round`, otherwise the next one up.
+fn nice_number(x: f32, round: bool) -> f32 {
+    let power = 10f32.powf(x.log10().floor());
+    let fraction = x / power;
+    let nice = if round {
+        match fraction {
+            f if f < 1.5 => 1.0,
+            f if f < 3.0 => 2.0,
+            f if f < 7.0 => 5.0,
+            _ => 10.0,
+        }
+    } else {
+        match fraction {
+            f if f <= 1.0 => 1.0,
+            f if f <= 2.0 => 2.0,
+            f if f <= 5.0 => 5.0,
+            _ => 10.0,
+        }
+    };
+    nice * power
+}
+
+/// Evenly spaced round values covering `min..=max`, and their spacing.
+///
+/// The first and last ticks are the nice values at or just beyond `min` and
+/// `max`, so the axis they span encloses the range. An empty or non-finite
+/// range gets a single tick in its middle, with a spacing of zero.
+pub fn nice_ticks(min: f32, max: f32) -> (Vec<f32>, f32) {
+    let range = nice_number(max - min, false);
+    let step = nice_number(range / (TARGET_TICKS - 1) as f32, true);
+    if !(step > 0.0 && step.is_finite()) {
+        return (vec![min + (max - min) * 0.5], 0.0);
+    }
+    let first = (min / step).floor() * step;
+    let last = (max / step).ceil() * step;
+    let count = ((last - first) / step).round() as usize + 1;
+    let ticks = (0..count)
+        .map(|i| first + i as f32 * step)
+        // Avoid labels like "-0.0" from rounding error
+        .map(|tick| if tick.abs() < step * 1e-3 { 0.0 } else { tick })
+        .collect();
+    (ticks, step)
+}
+
+/// A tick value with just enough decimals for ticks `step` apart, or as
+/// many as it needs if there is no spacing.
+fn format_tick(value: f32, step: f32) -> String {
+    if !(step > 0.0 && step.is_finite()) {
+        return value.to_string();
+    }
+    let decimals = (-step.log10().floor()).clamp(0.0, MAX_TICK_DECIMALS) as usize;
+    format!("{:.*}", decimals, value)
+}
+
+/// Label every `n`th of the ticks at `anchors`, with `n` the smallest that
+/// keeps labels `width` by `height` CSS pixels clear of each other where
+/// `mvp` and a `viewport` of that many CSS pixels put them on screen.
+fn label_stride(anchors: &[[f32; 3]], width: f32, height: f32, mvp: &Mat4, viewport: [f32; 2]) -> usize {
+    let gap = height * LABEL_GAP;
+    let screen: Vec<_> = anchors
+        .iter()
+        .map(|&p| project_point(p, mvp).map(|ndc| [ndc[0] * viewport[0] * 0.5, ndc[1] * viewport[1] * 0.5]))
+        .collect();
+    let mut stride = 1;
+    for pair in screen.windows(2) {
+        let [Some(a), Some(b)] = [pair[0], pair[1]] else {
+            continue;
+        };
+        // Labels `n` ticks apart are clear once they're far enough apart either across or up
+        let across = (width + gap) / (b[0] - a[0]).abs();
+        let up = (height + gap) / (b[1] - a[1]).abs();
+        let needed = across.min(up).ceil().min(anchors.len() as f32) as usize;
+        stride = stride.max(needed);
+    }
+    stride
+}
+
+/// Check and assemble `set_axes_style`'s arguments: widths and sizes must be
+/// positive and colors rgb or rgba, with empty colors keeping the defaults.
+pub fn parse_style(
+    axis_width: f32,
+    grid_width: f32,
+    label_size: f32,
+    axis_colors: &[f32],
+    grid_color: &[f32],
+    label_color: &[f32],
+) -> Result<AxesStyle, String> {
+    for (name, value) in [("Axis width", axis_width), ("Grid width", grid_width), ("Label size", label_size)] {
+        if !(value.is_finite() && value > 0.0) {
+            return Err(format!("{} must be a positive number", name));
+        }
+    }
+    let defaults = AxesStyle::default();
+    let colors = |values: &[f32], count: usize, name: &str| -> Result<Option<Vec<[f32; 4]>>, String> {
+        match values.len() {
+            0 => Ok(None),
+            len if len == count * 3 => Ok(Some(values.chunks_exact(3).map(|c| [c[0], c[1], c[2], 1.0]).collect())),
+            len if len == count * 4 => Ok(Some(values.chunks_exact(4).map(|c| [c[0], c[1], c[2], c[3]]).collect())),
+            len => Err(format!("Expected {} or {} values for {}, got {}", count * 3, count * 4, name, len)),
+        }
+    };
+    let axis_colors = colors(axis_colors, 3, "the axis colors")?;
+    let grid_color = colors(grid_color, 1, "the grid color")?;
+    let label_color = colors(label_color, 1, "the label color")?;
+    Ok(AxesStyle {
+        axis_width,
+        grid_width,
+        label_size,
+        axis_colors: axis_colors.map_or(defaults.axis_colors, |c| [c[0], c[1], c[2]]),
+        grid_color: grid_color.map_or(defaults.grid_color, |c| c[0]),
+        label_color: label_color.map_or(defaults.label_color, |c| c[0]),
+    })
+}
+
+/// Line segments and label glyphs for the axes around scene-space `bounds`.
+///
+/// The axes start at the lowest corner of the box spanned by the outermost
+/// ticks and run along its edges; tick marks and labels point away from the
+/// box so they stay clear of the grid and of each other at the corner.
+/// Where `mvp` (scene to clip space) squeezes an axis's ticks closer on a
+/// `viewport` of that many CSS pixels than its labels are wide, only every
+/// few ticks is labeled.
+pub fn axes_geometry(
+    settings: &AxesSettings,
+    bounds: &Bounds,
+    mvp: &Mat4,
+    viewport: [f32; 2],
+) -> (Vec<LineSegment>, Vec<GlyphInstance>) {
+    let style = &settings.style;
+    // A flat scene still gets a short axis across its thin side
+    let magnitude = bounds.min.iter().chain(&bounds.max).fold(0.0, |m: f32, c| m.max(c.abs()));
+    let min_extent = (bounds.radius * 0.5).max(magnitude * MIN_RELATIVE_EXTENT).max(1e-6);
+    let ticks: Vec<(Vec<f32>, f32)> = (0..3)
+        .map(|axis| {
+            let (min, max) = (bounds.min[axis], bounds.max[axis]);
+            let pad = ((min_extent - (max - min)) * 0.5).max(0.0);
+            nice_ticks(min - pad, max + pad)
+        })
+        .collect();
+    let first = [0, 1, 2].map(|axis| ticks[axis].0[0]);
+    let last = [0, 1, 2].map(|axis| *ticks[axis].0.last().unwrap_or(&first[axis]));
+    let tick_length = TICK_LENGTH * (0..3).map(|axis| last[axis] - first[axis]).fold(0.0, f32::max);
+
+    let mut lines = Vec::new();
+    let mut line = |from: [f32; 3], to: [f32; 3], width: f32, color: [f32; 4]| {
+        let polyline = Polyline { id: AXES_LINE_ID, positions: vec![from, to], colors: vec![color; 2], width };
+        lines.extend(polyline.segments());
+    };
+    // Point on the axis `axis` at `value`, along the box edge through the first corner
+    let on_axis = |axis: usize, value: f32| {
+        let mut p = first;
+        p[axis] = value;
+        p
+    };
+
+    // Grid lines across each plane through the first corner, at every tick of both its axes
+    let planes: &[(usize, usize)] = match settings.grid {
+        GridMode::None => &[],
+        GridMode::Ground => &[(0, 2)],
+        GridMode::Box => &[(0, 2), (0, 1), (1, 2)],
+    };
+    for &(a, b) in planes {
+        for (along, across) in [(a, b), (b, a)] {
+            for &tick in &ticks[across].0 {
+                let start = on_axis(across, tick);
+                let mut end = start;
+                end[along] = last[along];
+                line(start, end, style.grid_width, style.grid_color);
+            }
+        }
+    }
+
+    let mut glyphs = Vec::new();
+    for axis in 0..3 {
+        let color = style.axis_colors[axis];
+        // Away from the box, diagonally between the other two axes
+        let mut outward = [0.0; 3];
+        for other in (0..3).filter(|&other| other != axis) {
+            outward[other] = -std::f32::consts::FRAC_1_SQRT_2;
+        }
+        let offset = |p: [f32; 3], distance: f32| [0, 1, 2].map(|i| p[i] + outward[i] * distance);
+
+        if settings.axes {
+            line(first, on_axis(axis, last[axis]), style.axis_width, color);
+            let name_position = offset(on_axis(axis, last[axis] + tick_length * LABEL_DISTANCE), tick_length);
+            glyphs.extend(layout_text(["X", "Y", "Z"][axis], name_position, style.label_size * 1.25, color));
+        }
+        let (values, step) = &ticks[axis];
+        if settings.ticks {
+            for &value in values {
+                let p = on_axis(axis, value);
+                line(p, offset(p, tick_length), style.axis_width, color);
+            }
+        }
+        if settings.labels {
+            let labels: Vec<String> = values.iter().map(|&value| format_tick(value, *step)).collect();
+            let anchors: Vec<[f32; 3]> =
+                values.iter().map(|&value| offset(on_axis(axis, value), tick_length * LABEL_DISTANCE)).collect();
+            let width = labels.iter().map(|label| text_width(label, style.label_size)).fold(0.0, f32::max);
+            let stride = label_stride(&anchors, width, style.label_size, mvp, viewport);
+            for (label, &anchor) in labels.iter().zip(&anchors).step_by(stride) {
+                glyphs.extend(layout_text(label, anchor, style.label_size, style.label_color));
+            }
+        }
+    }
+    (lines, glyphs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::{mat4_mul, mat4_orthographic, mat4_translate};
+
+    fn settings() -> AxesSettings {
+        AxesSettings { axes: true, grid: GridMode::Ground, ticks: true, labels: true, style: AxesStyle::default() }
+    }
+
+    fn point_bounds(p: [f32; 3]) -> Bounds {
+        Bounds { min: p, max: p, center: p, radius: 0.0 }
+    }
+
+    #[test]
+    fn empty_range_gets_one_tick() {
+        assert_eq!(nice_ticks(100.0, 100.0), (vec![100.0], 0.0));
+        assert_eq!(nice_ticks(f32::NAN, f32::NAN).0.len(), 1);
+        assert_eq!(format_tick(100.0, 0.0), "100");
+        assert_eq!(format_tick(0.5, 1e-30), "0.500000000");
+    }
+
+    #[test]
+    fn single_point_far_from_the_origin_gets_axes() {
+        let bounds = point_bounds([100.0, 0.0, 0.0]);
+        let mvp = mat4_mul(mat4_translate([-100.0, 0.0, -1.0]), mat4_orthographic(1.0, 1.0, 0.1, 10.0));
+        let (lines, glyphs) = axes_geometry(&settings(), &bounds, &mvp, [500.0, 500.0]);
+        assert!(!lines.is_empty() && !glyphs.is_empty());
+    }
+
+    #[test]
+    fn labels_too_close_on_screen_are_thinned() {
+        let bounds = Bounds { min: [0.0; 3], max: [1.0; 3], center: [0.5; 3], radius: 0.87 };
+        let mvp = mat4_mul(mat4_translate([-0.5, -0.5, -2.0]), mat4_orthographic(1.0, 1.0, 0.1, 10.0));
+        let label_glyphs = |viewport| {
+            let settings = AxesSettings { axes: false, ticks: false, ..settings() };
+            axes_geometry(&settings, &bounds, &mvp, viewport).1.len()
+        };
+        // Ticks 0.0, 0.2, ... 1.0 of 3 glyphs each, all labeled along X and Y,
+        // and once on Z, which is seen end on
+        assert_eq!(label_glyphs([2000.0, 2000.0]), 3 * (6 + 6 + 1));
+        assert!(label_glyphs([100.0, 100.0]) < label_glyphs([2000.0, 2000.0]));
+    }
+}
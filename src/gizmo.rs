@@ -0,0 +1,114 @@
+//! Orientation gizmo: an XYZ triad in the bottom-right corner of the canvas
+//! that turns with the model, and the views its axis ends snap to when clicked.
+
+use crate::axes::AXES_LINE_ID;
+use crate::glyphs::layout_text;
+use crate::math::{mat4_from_quat, mat4_mul, mat4_orthographic, mat4_translate, transform_point, Mat4, Quat};
+use crate::model::Polyline;
+use crate::state::{InteractionState, ViewPreset};
+use crate::vertex::{GlyphInstance, LineSegment};
+
+/// Side of the gizmo's square viewport, in CSS pixels.
+pub const GIZMO_SIZE: f32 = 100.0;
+/// Gap between the viewport and the canvas edges, in CSS pixels.
+const GIZMO_MARGIN: f32 = 10.0;
+/// Axis length, and how far out the axis names sit, in half viewports.
+const AXIS_LENGTH: f32 = 0.7;
+const LABEL_DISTANCE: f32 = 0.85;
+const AXIS_WIDTH: f32 = 3.0;
+const LABEL_SIZE: f32 = 12.0;
+/// Negative axes are drawn in their axis color scaled by this.
+const NEGATIVE_SHADE: f32 = 0.5;
+/// How close a click must come to an axis end, in CSS pixels.
+const PICK_RADIUS: f32 = 10.0;
+
+/// The six axis ends as `(axis, sign)`, and the views looking down them at
+/// the origin, that side of the model facing the camera.
+const AXIS_VIEWS: [(usize, f32, ViewPreset); 6] = [
+    (0, 1.0, ViewPreset::Right),
+    (0, -1.0, ViewPreset::Left),
+    (1, 1.0, ViewPreset::Top),
+    (1, -1.0, ViewPreset::Bottom),
+    (2, 1.0, ViewPreset::Front),
+    (2, -1.0, ViewPreset::Back),
+];
+
+/// The gizmo's viewport as `[x, y, width, height]` in CSS pixels from the
+/// canvas's top-left corner.
+pub fn viewport(canvas_width: f32, canvas_height: f32) -> [f32; 4] {
+    [canvas_width - GIZMO_SIZE - GIZMO_MARGIN, canvas_height - GIZMO_SIZE - GIZMO_MARGIN, GIZMO_SIZE, GIZMO_SIZE]
+}
+
+/// Matrix taking the triad, in model axes, to its viewport's clip space:
+/// the model's rotation without its zoom or pan, viewed orthographically.
+pub fn gizmo_mvp(rotation: Quat) -> Mat4 {
+    let view = mat4_mul(mat4_from_quat(rotation), mat4_translate([0.0, 0.0, -2.0]));
+    mat4_mul(view, mat4_orthographic(1.0, 1.0, 1.0, 3.0))
+}
+
+/// Axis lines and names of the triad, the positive axes in `axis_colors`
+/// and named, the negative ones shaded.
+pub fn gizmo_geometry(axis_colors: &[[f32; 4]; 3]) -> (Vec<LineSegment>, Vec<GlyphInstance>) {
+    let mut lines = Vec::new();
+    let mut glyphs = Vec::new();
+    for &(axis, sign, _) in &AXIS_VIEWS {
+        let along = |distance: f32| {
+            let mut p = [0.0; 3];
+            p[axis] = sign * distance;
+            p
+        };
+        let [r, g, b, a] = axis_colors[axis];
+        let shade = if sign > 0.0 { 1.0 } else { NEGATIVE_SHADE };
+        let color = [r * shade, g * shade, b * shade, a];
+        let positions = vec![[0.0; 3], along(AXIS_LENGTH)];
+        lines.extend(Polyline { id: AXES_LINE_ID, positions, colors: vec![color; 2], width: AXIS_WIDTH }.segments());
+        if sign > 0.0 {
+            glyphs.extend(layout_text(["X", "Y", "Z"][axis], along(LABEL_DISTANCE), LABEL_SIZE, color));
+        }
+    }
+    (lines, glyphs)
+}
+
+/// The view for the axis end under canvas point `(x, y)` in CSS pixels, if
+/// the gizmo is shown and one is within reach, the nearest to the viewer
+/// where they overlap. An end reaches from the axis tip out to where its
+/// name sits.
+pub fn pick_axis(x: f32, y: f32, state: &InteractionState) -> Option<ViewPreset> {
+    if !state.show_gizmo {
+        return None;
+    }
+    let [left, top, width, height] = viewport(state.canvas_width as f32, state.canvas_height as f32);
+    if x < left || x > left + width || y < top || y > top + height {
+        return None;
+    }
+    let mvp = gizmo_mvp(state.rotation);
+    // CSS pixels from the viewport's center, y up
+    let to_screen = |p: [f32; 3]| {
+        let clip = transform_point(p, &mvp);
+        ([clip[0] * width * 0.5, clip[1] * height * 0.5], clip[2])
+    };
+    let click = [x - left - width * 0.5, top + height * 0.5 - y];
+
+    AXIS_VIEWS
+        .iter()
+        .filter_map(|&(axis, sign, view)| {
+            let mut tip = [0.0; 3];
+            tip[axis] = sign * AXIS_LENGTH;
+            let mut label = [0.0; 3];
+            label[axis] = sign * LABEL_DISTANCE;
+            let ((a, depth), (b, _)) = (to_screen(tip), to_screen(label));
+            (segment_distance(click, a, b) <= PICK_RADIUS).then_some((depth, view))
+        })
+        .min_by(|(a, _), (b, _)| a.total_cmp(b))
+        .map(|(_, view)| view)
+}
+
+/// Distance from `p` to the segment from `a` to `b`, on screen.
+fn segment_distance(p: [f32; 2], a: [f32; 2], b: [f32; 2]) -> f32 {
+    let ab = [b[0] - a[0], b[1] - a[1]];
+    let ap = [p[0] - a[0], p[1] - a[1]];
+    let length_sq = ab[0] * ab[0] + ab[1] * ab[1];
+    let t = if length_sq > 0.0 { ((ap[0] * ab[0] + ap[1] * ab[1]) / length_sq).clamp(0.0, 1.0) } else { 0.0 };
+    let (dx, dy) = (ap[0] - ab[0] * t, ap[1] - ab[1] * t);
+    (dx * dx + dy * dy).sqrt()
+}
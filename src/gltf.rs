@@ -0,0 +1,442 @@
+//! glTF 2.0 / GLB scene import.
+
+use base64::Engine;
+use serde_json::Value;
+
+use crate::math::{
+    mat4_determinant3, mat4_from_quat, mat4_mul, mat4_normal_matrix, mat4_scale_xyz, mat4_translate,
+    normalize, transform_point, transform_vector, Mat4, MAT4_IDENTITY,
+};
+use crate::model::{expand_triangles, triangle_normal, ModelIndices, RawVertex};
+use crate::vertex::Vertex;
+
+const GLB_MAGIC: &[u8; 4] = b"glTF";
+const GLB_CHUNK_JSON: u32 = 0x4E4F_534A;
+const GLB_CHUNK_BIN: u32 = 0x004E_4942;
+const PRIMITIVE_MODE_TRIANGLES: u64 = 4;
+/// Most elements an accessor without a bufferView (all zeros) may claim.
+const MAX_ZERO_ACCESSOR_COUNT: usize = 1 << 24;
+
+/// Parse a `.gltf` (JSON) or `.glb` (binary) file into vertices and indices.
+///
+/// Walks the default scene's node hierarchy, baking each node's world
+/// transform into its mesh's triangle primitives. Reads `POSITION`, `NORMAL`
+/// and `COLOR_0` attributes, multiplied by the material's `baseColorFactor`.
+/// Buffers must be embedded as data URIs or in the GLB binary chunk.
+pub fn parse_gltf(bytes: &[u8]) -> Result<(Vec<Vertex>, ModelIndices), String> {
+    let (json, bin_chunk) = if bytes.starts_with(GLB_MAGIC) {
+        split_glb(bytes)?
+    } else {
+        (bytes, None)
+    };
+    let doc: Value = serde_json::from_slice(json).map_err(|e| format!("Invalid glTF JSON: {}", e))?;
+    let buffers = load_buffers(&doc, bin_chunk)?;
+
+    let nodes = doc["nodes"].as_array().map(Vec::as_slice).unwrap_or(&[]);
+    let roots: Vec<usize> = match doc["scenes"].get(doc["scene"].as_u64().unwrap_or(0) as usize) {
+        Some(scene) => index_array(&scene["nodes"]),
+        // No scenes: every node that is nobody's child is a root
+        None => {
+            let children: Vec<usize> = nodes.iter().flat_map(|n| index_array(&n["children"])).collect();
+            (0..nodes.len()).filter(|i| !children.contains(i)).collect()
+        }
+    };
+
+    let mut triangles: Vec<[RawVertex; 3]> = Vec::new();
+    let mut stack: Vec<(usize, Mat4, usize)> = roots.into_iter().map(|n| (n, MAT4_IDENTITY, 0)).collect();
+    while let Some((node_idx, parent, depth)) = stack.pop() {
+        if depth > nodes.len() {
+            return Err("glTF node hierarchy contains a cycle".to_string());
+        }
+        let node = nodes.get(node_idx).ok_or_else(|| format!("glTF node {} does not exist", node_idx))?;
+        let world = mat4_mul(node_transform(node), parent);
+
+        if let Some(mesh_idx) = node["mesh"].as_u64() {
+            let mesh = doc["meshes"]
+                .get(mesh_idx as usize)
+                .ok_or_else(|| format!("glTF mesh {} does not exist", mesh_idx))?;
+            for primitive in mesh["primitives"].as_array().map(Vec::as_slice).unwrap_or(&[]) {
+                read_primitive(&doc, &buffers, primitive, &world, &mut triangles)?;
+            }
+        }
+        for child in index_array(&node["children"]) {
+            stack.push((child, world, depth + 1));
+        }
+    }
+
+    if triangles.is_empty() {
+        return Err("No triangle primitives found in glTF scene".to_string());
+    }
+
+    Ok(expand_triangles(&triangles))
+}
+
+/// Split a GLB container into its JSON chunk and optional binary chunk.
+fn split_glb(bytes: &[u8]) -> Result<(&[u8], Option<&[u8]>), String> {
+    let read_u32 = |at: usize| -> Result<u32, String> {
+        bytes
+            .get(at..at + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .ok_or_else(|| "GLB truncated".to_string())
+    };
+
+    let version = read_u32(4)?;
+    if version != 2 {
+        return Err(format!("Unsupported GLB version {}", version));
+    }
+    let length = (read_u32(8)? as usize).min(bytes.len());
+
+    let mut json = None;
+    let mut bin = None;
+    let mut offset = 12;
+    while offset + 8 <= length {
+        let chunk_length = read_u32(offset)? as usize;
+        let chunk_type = read_u32(offset + 4)?;
+        let data = bytes
+            .get(offset + 8..offset + 8 + chunk_length)
+            .ok_or("GLB chunk runs past end of file")?;
+        match chunk_type {
+            GLB_CHUNK_JSON => json = Some(data),
+            GLB_CHUNK_BIN => bin = Some(data),
+            _ => {}
+        }
+        offset += 8 + chunk_length;
+    }
+
+    Ok((json.ok_or("GLB has no JSON chunk")?, bin))
+}
+
+/// Resolve every buffer to its bytes (GLB chunk or base64 data URI).
+fn load_buffers(doc: &Value, bin_chunk: Option<&[u8]>) -> Result<Vec<Vec<u8>>, String> {
+    let mut buffers = Vec::new();
+    for (i, buffer) in doc["buffers"].as_array().map(Vec::as_slice).unwrap_or(&[]).iter().enumerate() {
+        let data = match buffer["uri"].as_str() {
+            None if i == 0 => bin_chunk.ok_or("glTF buffer 0 has no uri and no GLB binary chunk")?.to_vec(),
+            None => return Err(format!("glTF buffer {} has no uri", i)),
+            Some(uri) if uri.starts_with("data:") => {
+                let (_, payload) = uri.split_once(";base64,").ok_or("glTF data URI is not base64")?;
+                base64::engine::general_purpose::STANDARD
+                    .decode(payload)
+                    .map_err(|e| format!("glTF buffer {}: {}", i, e))?
+            }
+            Some(uri) => return Err(format!("External glTF buffer '{}' is not supported; embed it or use GLB", uri)),
+        };
+        buffers.push(data);
+    }
+    Ok(buffers)
+}
+
+/// A node's local transform from `matrix` or `translation`/`rotation`/`scale`.
+fn node_transform(node: &Value) -> Mat4 {
+    if let Some(m) = float_array::<16>(&node["matrix"]) {
+        // glTF matrices are column-major, matching Mat4's [column][row] layout
+        return [
+            [m[0], m[1], m[2], m[3]],
+            [m[4], m[5], m[6], m[7]],
+            [m[8], m[9], m[10], m[11]],
+            [m[12], m[13], m[14], m[15]],
+        ];
+    }
+    let translation = float_array::<3>(&node["translation"]).unwrap_or([0.0; 3]);
+    let rotation = float_array::<4>(&node["rotation"]).unwrap_or([0.0, 0.0, 0.0, 1.0]);
+    let scale = float_array::<3>(&node["scale"]).unwrap_or([1.0; 3]);
+    // T * R * S
+    mat4_mul(mat4_mul(mat4_scale_xyz(scale), mat4_from_quat(rotation)), mat4_translate(translation))
+}
+
+/// Append one mesh primitive's triangles, transformed into world space.
+fn read_primitive(
+    doc: &Value,
+    buffers: &[Vec<u8>],
+    primitive: &Value,
+    world: &Mat4,
+    triangles: &mut Vec<[RawVertex; 3]>,
+) -> Result<(), String> {
+    if primitive["mode"].as_u64().unwrap_or(PRIMITIVE_MODE_TRIANGLES) != PRIMITIVE_MODE_TRIANGLES {
+        return Ok(());
+    }
+    let attributes = &primitive["attributes"];
+    let position_idx = attributes["POSITION"].as_u64().ok_or("glTF primitive has no POSITION")?;
+    let positions = read_accessor(doc, buffers, position_idx as usize)?;
+    let normals = match attributes["NORMAL"].as_u64() {
+        Some(idx) => Some(read_accessor(doc, buffers, idx as usize)?),
+        None => None,
+    };
+    let colors = match attributes["COLOR_0"].as_u64() {
+        Some(idx) => Some(read_accessor(doc, buffers, idx as usize)?),
+        None => None,
+    };
+    let indices: Vec<usize> = match primitive["indices"].as_u64() {
+        Some(idx) => read_indices(doc, buffers, idx as usize)?.into_iter().map(|i| i as usize).collect(),
+        None => (0..positions.len()).collect(),
+    };
+
+    let base_color = primitive["material"]
+        .as_u64()
+        .and_then(|m| float_array::<4>(&doc["materials"][m as usize]["pbrMetallicRoughness"]["baseColorFactor"]))
+        .unwrap_or([1.0; 4]);
+    let normal_matrix = mat4_normal_matrix(world);
+    let mirrored = mat4_determinant3(world) < 0.0;
+
+    for tri in indices.chunks_exact(3) {
+        if let Some(&idx) = tri.iter().find(|&&idx| idx >= positions.len()) {
+            return Err(format!("glTF index {} out of range ({} vertices)", idx, positions.len()));
+        }
+        // Mirroring transforms flip winding; swap two corners to stay counter-clockwise
+        let tri = if mirrored { [tri[0], tri[2], tri[1]] } else { [tri[0], tri[1], tri[2]] };
+        let corners = tri.map(|idx| {
+            let p = positions.get(idx).unwrap_or(&[0.0; 3]);
+            transform_point([p[0], p[1], p[2]], world)
+        });
+        let face_normal = triangle_normal(corners[0], corners[1], corners[2]);
+
+        let mut triangle = [([0.0; 3], [0.0; 3], [0.0; 4]); 3];
+        for (corner, &idx) in tri.iter().enumerate() {
+            let normal = match normals.as_ref().and_then(|n| n.get(idx)) {
+                Some(n) => normalize(transform_vector([n[0], n[1], n[2]], &normal_matrix)),
+                None => face_normal,
+            };
+            let vertex_color = colors
+                .as_ref()
+                .and_then(|c| c.get(idx))
+                .map_or([1.0; 4], |c| [c[0], c[1], c[2], c.get(3).copied().unwrap_or(1.0)]);
+            let color = [0, 1, 2, 3].map(|i| vertex_color[i] * base_color[i]);
+            triangle[corner] = (corners[corner], normal, color);
+        }
+        triangles.push(triangle);
+    }
+
+    Ok(())
+}
+
+/// Accessor data decoded to floats, `components` values per element.
+struct AccessorData {
+    values: Vec<f32>,
+    components: usize,
+}
+
+impl AccessorData {
+    fn len(&self) -> usize {
+        self.values.len() / self.components
+    }
+
+    fn get(&self, i: usize) -> Option<&[f32]> {
+        self.values.get(i * self.components..(i + 1) * self.components)
+    }
+}
+
+/// Where and how an accessor's elements are stored.
+struct AccessorLayout<'a> {
+    count: usize,
+    components: usize,
+    component_type: u64,
+    component_size: usize,
+    normalized: bool,
+    /// The buffer, first byte and byte stride; `None` for all-zero accessors.
+    source: Option<(&'a [u8], usize, usize)>,
+}
+
+impl AccessorLayout<'_> {
+    /// Bytes of component `c` of element `i`, in bounds as checked by `accessor_layout`.
+    fn component(&self, i: usize, c: usize) -> &[u8] {
+        let (buffer, start, stride) = self.source.unwrap_or_default();
+        let at = start + i * stride + c * self.component_size;
+        &buffer[at..at + self.component_size]
+    }
+}
+
+/// Look up an accessor and check that all its elements lie inside its
+/// bufferView, so the count can be trusted before allocating for it.
+fn accessor_layout<'a>(doc: &Value, buffers: &'a [Vec<u8>], index: usize) -> Result<AccessorLayout<'a>, String> {
+    let accessor = doc["accessors"].get(index).ok_or_else(|| format!("glTF accessor {} does not exist", index))?;
+    if accessor.get("sparse").is_some() {
+        return Err(format!("glTF accessor {}: sparse accessors are not supported", index));
+    }
+    let count = accessor["count"].as_u64().ok_or("glTF accessor has no count")? as usize;
+    let components = match accessor["type"].as_str() {
+        Some("SCALAR") => 1,
+        Some("VEC2") => 2,
+        Some("VEC3") => 3,
+        Some("VEC4") => 4,
+        other => return Err(format!("glTF accessor {}: unsupported type {:?}", index, other)),
+    };
+    let component_type = accessor["componentType"].as_u64().unwrap_or(0);
+    let component_size = match component_type {
+        5120 | 5121 => 1,
+        5122 | 5123 => 2,
+        5125 | 5126 => 4,
+        other => return Err(format!("glTF accessor {}: unsupported componentType {}", index, other)),
+    };
+    let normalized = accessor["normalized"].as_bool().unwrap_or(false);
+    let element_size = components * component_size;
+
+    // Accessors without a bufferView are all zeros
+    let Some(view_idx) = accessor["bufferView"].as_u64() else {
+        if count > MAX_ZERO_ACCESSOR_COUNT {
+            return Err(format!("glTF accessor {}: {} elements without a bufferView", index, count));
+        }
+        return Ok(AccessorLayout { count, components, component_type, component_size, normalized, source: None });
+    };
+    let view = &doc["bufferViews"][view_idx as usize];
+    let buffer = buffers
+        .get(view["buffer"].as_u64().unwrap_or(0) as usize)
+        .ok_or_else(|| format!("glTF bufferView {} references a missing buffer", view_idx))?;
+    let view_start = view["byteOffset"].as_u64().unwrap_or(0) as usize;
+    let view_end = match view["byteLength"].as_u64() {
+        Some(length) => view_start.checked_add(length as usize),
+        None => Some(buffer.len()),
+    };
+    let view_end = view_end
+        .filter(|&end| view_start <= end && end <= buffer.len())
+        .ok_or_else(|| format!("glTF bufferView {} lies outside its buffer", view_idx))?;
+    let start = view_start
+        .checked_add(accessor["byteOffset"].as_u64().unwrap_or(0) as usize)
+        .ok_or_else(|| format!("glTF accessor {} reads past end of buffer", index))?;
+    let stride = view["byteStride"].as_u64().map_or(element_size, |s| s as usize);
+
+    // The last element ends (count - 1) strides and one element past the start
+    let end = match count.checked_sub(1) {
+        Some(last) => last
+            .checked_mul(stride)
+            .and_then(|b| b.checked_add(start))
+            .and_then(|b| b.checked_add(element_size)),
+        None => Some(start),
+    };
+    if end.is_none_or(|end| end > view_end) {
+        return Err(format!("glTF accessor {} reads past end of its bufferView", index));
+    }
+    Ok(AccessorLayout {
+        count,
+        components,
+        component_type,
+        component_size,
+        normalized,
+        source: Some((buffer, start, stride)),
+    })
+}
+
+/// Read an accessor (1 to 4 components per element) as floats.
+///
+/// Normalized integer components are mapped to 0..1 (or -1..1 for signed).
+fn read_accessor(doc: &Value, buffers: &[Vec<u8>], index: usize) -> Result<AccessorData, String> {
+    let layout = accessor_layout(doc, buffers, index)?;
+    let components = layout.components;
+    if layout.source.is_none() {
+        return Ok(AccessorData { values: vec![0.0; layout.count * components], components });
+    }
+
+    let mut values = Vec::with_capacity(layout.count * components);
+    for i in 0..layout.count {
+        for c in 0..components {
+            let b = layout.component(i, c);
+            let value = match layout.component_type {
+                5120 if layout.normalized => (b[0] as i8 as f32 / 127.0).max(-1.0),
+                5120 => b[0] as i8 as f32,
+                5121 if layout.normalized => b[0] as f32 / 255.0,
+                5121 => b[0] as f32,
+                5122 if layout.normalized => (i16::from_le_bytes([b[0], b[1]]) as f32 / 32767.0).max(-1.0),
+                5122 => i16::from_le_bytes([b[0], b[1]]) as f32,
+                5123 if layout.normalized => u16::from_le_bytes([b[0], b[1]]) as f32 / 65535.0,
+                5123 => u16::from_le_bytes([b[0], b[1]]) as f32,
+                5125 => u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f32,
+                _ => f32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+            };
+            values.push(value);
+        }
+    }
+    Ok(AccessorData { values, components })
+}
+
+/// Read an index accessor: unsigned byte, short or int scalars, exactly.
+fn read_indices(doc: &Value, buffers: &[Vec<u8>], index: usize) -> Result<Vec<u32>, String> {
+    let layout = accessor_layout(doc, buffers, index)?;
+    if layout.components != 1 || !matches!(layout.component_type, 5121 | 5123 | 5125) {
+        return Err(format!("glTF accessor {}: indices must be unsigned integer scalars", index));
+    }
+    if layout.source.is_none() {
+        return Ok(vec![0; layout.count]);
+    }
+    Ok((0..layout.count)
+        .map(|i| match layout.component(i, 0) {
+            [b0] => *b0 as u32,
+            [b0, b1] => u16::from_le_bytes([*b0, *b1]) as u32,
+            b => u32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+        })
+        .collect())
+}
+
+fn index_array(value: &Value) -> Vec<usize> {
+    value
+        .as_array()
+        .map(|a| a.iter().filter_map(Value::as_u64).map(|i| i as usize).collect())
+        .unwrap_or_default()
+}
+
+fn float_array<const N: usize>(value: &Value) -> Option<[f32; N]> {
+    let array = value.as_array()?;
+    if array.len() != N {
+        return None;
+    }
+    let mut out = [0.0; N];
+    for (o, v) in out.iter_mut().zip(array) {
+        *o = v.as_f64()? as f32;
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A one-triangle glTF whose index accessor claims `index_count` u32
+    /// elements, with 3 positions and the indices 0, 1, 2 in the buffer.
+    fn triangle_gltf(index_count: u64) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        for p in [[0.0f32, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]] {
+            buffer.extend(p.iter().flat_map(|v| v.to_le_bytes()));
+        }
+        buffer.extend([0u32, 1, 2].iter().flat_map(|i| i.to_le_bytes()));
+        let data = base64::engine::general_purpose::STANDARD.encode(&buffer);
+        format!(
+            r#"{{
+                "buffers": [{{"uri": "data:application/octet-stream;base64,{data}", "byteLength": 48}}],
+                "bufferViews": [
+                    {{"buffer": 0, "byteOffset": 0, "byteLength": 36}},
+                    {{"buffer": 0, "byteOffset": 36, "byteLength": 12}}
+                ],
+                "accessors": [
+                    {{"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3"}},
+                    {{"bufferView": 1, "componentType": 5125, "count": {index_count}, "type": "SCALAR"}}
+                ],
+                "meshes": [{{"primitives": [{{"attributes": {{"POSITION": 0}}, "indices": 1}}]}}],
+                "nodes": [{{"mesh": 0}}]
+            }}"#
+        )
+        .into_bytes()
+    }
+
+    #[test]
+    fn gltf_reads_u32_indices() {
+        let (vertices, _) = parse_gltf(&triangle_gltf(3)).unwrap();
+        assert_eq!(vertices.len(), 3);
+    }
+
+    #[test]
+    fn gltf_accessor_count_beyond_its_buffer_view_is_an_error() {
+        for count in [4, 1 << 40, u64::MAX / 4] {
+            let Err(error) = parse_gltf(&triangle_gltf(count)) else {
+                panic!("count {} parsed", count);
+            };
+            assert!(error.contains("past end"), "{}", error);
+        }
+    }
+
+    #[test]
+    fn gltf_node_with_missing_mesh_is_an_error() {
+        let gltf = String::from_utf8(triangle_gltf(3)).unwrap().replace(r#"{"mesh": 0}"#, r#"{"mesh": 1}"#);
+        let Err(error) = parse_gltf(gltf.as_bytes()) else {
+            panic!("node with a missing mesh parsed");
+        };
+        assert_eq!(error, "glTF mesh 1 does not exist");
+    }
+}
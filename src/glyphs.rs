@@ -0,0 +1,90 @@
+//! Bitmap font glyph atlas and text layout for labels.
+
+use crate::vertex::GlyphInstance;
+
+/// Characters the font has, in atlas order.
+const CHARACTERS: &str = "0123456789.-XYZ";
+
+// Glyphs are 5x7 font pixels, one row per byte from the top, high bit on the left
+const GLYPH_WIDTH: u32 = 5;
+const GLYPH_HEIGHT: u32 = 7;
+const FONT: [[u8; 7]; 15] = [
+    [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E], // 0
+    [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E], // 1
+    [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F], // 2
+    [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E], // 3
+    [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02], // 4
+    [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E], // 5
+    [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E], // 6
+    [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08], // 7
+    [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E], // 8
+    [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C], // 9
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C], // .
+    [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00], // -
+    [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11], // X
+    [0x11, 0x11, 0x0A, 0x04, 0x04, 0x04, 0x04], // Y
+    [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F], // Z
+];
+
+/// Atlas texels per font pixel, so linear filtering smooths the edges of
+/// labels drawn at sizes between whole multiples of the font.
+const ATLAS_SCALE: u32 = 4;
+/// Empty font pixels around each glyph in its atlas cell, so filtering never
+/// picks up a neighbor.
+const CELL_PADDING: u32 = 1;
+pub const CELL_WIDTH: u32 = (GLYPH_WIDTH + 2 * CELL_PADDING) * ATLAS_SCALE;
+pub const CELL_HEIGHT: u32 = (GLYPH_HEIGHT + 2 * CELL_PADDING) * ATLAS_SCALE;
+/// Number of cells, side by side in one row.
+pub const GLYPH_COUNT: u32 = FONT.len() as u32;
+
+/// Coverage (0 or 255) of every atlas texel, rows top to bottom, for an
+/// `R8Unorm` texture `GLYPH_COUNT * CELL_WIDTH` by `CELL_HEIGHT` texels.
+pub fn atlas_pixels() -> Vec<u8> {
+    let width = GLYPH_COUNT * CELL_WIDTH;
+    let mut pixels = vec![0u8; (width * CELL_HEIGHT) as usize];
+    for (cell, rows) in FONT.iter().enumerate() {
+        for (row, bits) in rows.iter().enumerate() {
+            for column in 0..GLYPH_WIDTH {
+                if bits & (1 << (GLYPH_WIDTH - 1 - column)) == 0 {
+                    continue;
+                }
+                let x0 = cell as u32 * CELL_WIDTH + (column + CELL_PADDING) * ATLAS_SCALE;
+                let y0 = (row as u32 + CELL_PADDING) * ATLAS_SCALE;
+                for y in y0..y0 + ATLAS_SCALE {
+                    let start = (y * width + x0) as usize;
+                    pixels[start..start + ATLAS_SCALE as usize].fill(255);
+                }
+            }
+        }
+    }
+    pixels
+}
+
+/// Width in CSS pixels of `text` laid out `size` CSS pixels tall.
+pub fn text_width(text: &str, size: f32) -> f32 {
+    let glyphs = text.chars().filter(|&c| CHARACTERS.contains(c)).count();
+    // One font pixel of spacing between glyphs
+    let pixel = size / GLYPH_HEIGHT as f32;
+    (glyphs as f32 * (GLYPH_WIDTH + 1) as f32 * pixel - pixel).max(0.0)
+}
+
+/// One glyph quad per character of `text` the font has, centered on
+/// `anchor` (a scene-space point) on screen, `size` CSS pixels tall.
+pub fn layout_text(text: &str, anchor: [f32; 3], size: f32, color: [f32; 4]) -> Vec<GlyphInstance> {
+    let glyphs: Vec<u32> = text.chars().filter_map(|c| CHARACTERS.find(c).map(|i| i as u32)).collect();
+    let pixel = size / GLYPH_HEIGHT as f32;
+    let advance = (GLYPH_WIDTH + 1) as f32 * pixel;
+    let width = text_width(text, size);
+    let quad = [CELL_WIDTH as f32 / ATLAS_SCALE as f32 * pixel, CELL_HEIGHT as f32 / ATLAS_SCALE as f32 * pixel];
+    glyphs
+        .iter()
+        .enumerate()
+        .map(|(i, &glyph)| GlyphInstance {
+            anchor,
+            glyph,
+            offset: [-width * 0.5 + i as f32 * advance - CELL_PADDING as f32 * pixel, -quad[1] * 0.5],
+            size: quad,
+            color,
+        })
+        .collect()
+}
@@ -0,0 +1,361 @@
+//! Native offscreen rendering: load a model, place the camera, and read the
+//! frame back as RGBA pixels or a PNG, without a browser or window.
+
+use std::future::Future;
+use std::path::Path;
+use std::rc::Rc;
+use std::sync::mpsc;
+use std::task::{Context, Poll, Waker};
+
+use crate::math::{quat_from_euler_xy, quat_normalize, Quat};
+use crate::model::{
+    parse_model, parse_obj, parse_ply, parse_stl, point_vertices, polylines, SharedMesh, DEFAULT_COLOR,
+};
+use crate::png;
+use crate::renderer::{self, Readback, SceneRenderer};
+use crate::state::{
+    GpuResources, GridMode, InteractionState, LineCap, LineJoin, ModelResources, PointSizeMode, Projection,
+    RenderTarget, Transparency, ViewPreset, DEFAULT_SCALE, ZOOM_MAX, ZOOM_MIN,
+};
+use crate::upload::SceneUpload;
+use crate::{axes, gltf};
+
+/// Color format rendered to; sRGB-encoded like the canvas.
+const OFFSCREEN_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+/// Renders scenes offscreen on any adapter wgpu can find, including software
+/// ones such as lavapipe (Vulkan) or llvmpipe (GL).
+///
+/// The camera behaves as in the browser viewer, with the image size standing
+/// in for the canvas. Set `WGPU_BACKEND` (e.g. `vulkan` or `gl`) or
+/// `WGPU_ADAPTER_NAME` to choose the adapter.
+pub struct HeadlessRenderer {
+    gpu: GpuResources,
+    scene: SceneRenderer,
+    model: ModelResources,
+    state: InteractionState,
+    // Resolved frame, copied out by `render`
+    texture: wgpu::Texture,
+    target: RenderTarget,
+    transparent_background: bool,
+}
+
+impl HeadlessRenderer {
+    /// Create a renderer for `width` x `height` pixel images.
+    pub fn new(width: u32, height: u32) -> Result<Self, String> {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::from_env_or_default());
+        let adapter = block_on(wgpu::util::initialize_adapter_from_env_or_default(&instance, None))
+            .or_else(|| {
+                block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+                    power_preference: wgpu::PowerPreference::default(),
+                    compatible_surface: None,
+                    force_fallback_adapter: true,
+                }))
+            })
+            .ok_or("No GPU adapter found")?;
+        let (device, queue) = block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("Headless Device"),
+                // Software adapters fall short of WebGPU's default limits
+                required_limits: adapter.limits(),
+                ..Default::default()
+            },
+            None,
+        ))
+        .map_err(|e| format!("Failed to get device: {}", e))?;
+        let device = Rc::new(device);
+        let queue = Rc::new(queue);
+
+        let uniform_layout = Rc::new(renderer::create_uniform_layout(&device));
+        let shared_mesh_layout = Rc::new(renderer::create_shared_mesh_layout(&device));
+        let scene = SceneRenderer::new(device.clone(), queue, OFFSCREEN_FORMAT, &uniform_layout, &shared_mesh_layout);
+        check_size(&device, width, height)?;
+        let (texture, target) = create_offscreen_target(&device, width, height, scene.format);
+
+        let mut state = InteractionState::new(width, height);
+        // Nothing is animated between frames, so camera changes take effect at once
+        state.view_transition_ms = 0.0;
+
+        Ok(HeadlessRenderer {
+            gpu: GpuResources {
+                device,
+                uniform_layout,
+                shared_mesh_layout,
+            },
+            scene,
+            model: ModelResources { objects: Vec::new() },
+            state,
+            texture,
+            target,
+            transparent_background: false,
+        })
+    }
+
+    /// Change the image size. Does not refit the camera; call `fit_to_view`
+    /// to frame the scene at the new aspect ratio.
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<(), String> {
+        check_size(&self.gpu.device, width, height)?;
+        (self.texture, self.target) = create_offscreen_target(&self.gpu.device, width, height, self.scene.format);
+        self.state.canvas_width = width;
+        self.state.canvas_height = height;
+        Ok(())
+    }
+
+    /// Image size in pixels.
+    pub fn size(&self) -> (u32, u32) {
+        (self.target.width, self.target.height)
+    }
+
+    /// Load a model in the text format, replacing the scene, and frame it.
+    pub fn load_model(&mut self, model_text: &str) -> Result<(), String> {
+        let mesh = parse_model(model_text).map_err(|e| e.to_string())?;
+        self.scene_upload().replace_scene(|scene, id| scene.upload_shared_model(id, &mesh));
+        Ok(())
+    }
+
+    /// Load a Wavefront OBJ model, optionally colored by the materials in `mtl_text`.
+    pub fn load_obj(&mut self, obj_text: &str, mtl_text: Option<&str>) -> Result<(), String> {
+        let (vertices, indices) = parse_obj(obj_text, mtl_text)?;
+        self.scene_upload().replace_scene(|scene, id| scene.upload_model(id, &vertices, &indices, &[]));
+        Ok(())
+    }
+
+    /// Load a binary or ASCII STL model, every facet in `color` (RGBA) if given.
+    pub fn load_stl(&mut self, bytes: &[u8], color: Option<[f32; 4]>) -> Result<(), String> {
+        let (vertices, indices) = parse_stl(bytes, color.unwrap_or(DEFAULT_COLOR))?;
+        self.scene_upload().replace_scene(|scene, id| scene.upload_model(id, &vertices, &indices, &[]));
+        Ok(())
+    }
+
+    /// Load an ASCII or binary PLY mesh, or a point cloud if it has no faces,
+    /// optionally colored by the scalar vertex property `color_by` (see
+    /// `load_ply` in the browser API).
+    pub fn load_ply(&mut self, bytes: &[u8], color_by: Option<&str>) -> Result<(), String> {
+        let ply = parse_ply(bytes)?;
+        if ply.is_point_cloud() {
+            let points = ply.to_points(color_by)?;
+            self.scene_upload().replace_scene(|scene, id| scene.upload_points(id, &points));
+            return Ok(());
+        }
+        let (vertices, indices) = ply.to_mesh(color_by)?;
+        self.scene_upload().replace_scene(|scene, id| scene.upload_model(id, &vertices, &indices, &[]));
+        Ok(())
+    }
+
+    /// Load a point cloud from flat xyz positions, rgb or rgba colors (or
+    /// none) and optional size factors, replacing the scene, and frame it.
+    pub fn load_points(&mut self, positions: &[f32], colors: &[f32], sizes: Option<&[f32]>) -> Result<(), String> {
+        let points = point_vertices(positions, colors, sizes.unwrap_or(&[]))?;
+        self.scene_upload().replace_scene(|scene, id| scene.upload_points(id, &points));
+        Ok(())
+    }
+
+    /// Load polylines from flat xyz positions, rgb or rgba colors (or none)
+    /// and the number of points in each (empty for one polyline), `width`
+    /// CSS pixels wide, replacing the scene, and frame them.
+    pub fn load_polylines(
+        &mut self,
+        positions: &[f32],
+        colors: &[f32],
+        lengths: &[u32],
+        width: f32,
+    ) -> Result<(), String> {
+        let mesh = SharedMesh::from_lines(polylines(positions, colors, lengths, width)?);
+        self.scene_upload().replace_scene(|scene, id| scene.upload_shared_model(id, &mesh));
+        Ok(())
+    }
+
+    /// Load a glTF 2.0 scene from a `.gltf` (JSON) or `.glb` file's bytes.
+    pub fn load_gltf(&mut self, bytes: &[u8]) -> Result<(), String> {
+        let (vertices, indices) = gltf::parse_gltf(bytes)?;
+        self.scene_upload().replace_scene(|scene, id| scene.upload_model(id, &vertices, &indices, &[]));
+        Ok(())
+    }
+
+    /// Set the rotation from `x`, `y` Euler angles in radians (about X, then Y).
+    pub fn set_rotation(&mut self, x: f32, y: f32) {
+        self.state.rotation = quat_from_euler_xy(x, y);
+    }
+
+    /// Set the rotation from a quaternion `[x, y, z, w]`.
+    pub fn set_rotation_quaternion(&mut self, rotation: Quat) {
+        self.state.rotation = quat_normalize(rotation);
+    }
+
+    /// Turn to a standard view (`"front"`, `"top"`, `"isometric"`, ...),
+    /// centered on the scene at default zoom.
+    pub fn set_view(&mut self, name: &str) -> Result<(), String> {
+        let preset = ViewPreset::from_name(name).ok_or_else(|| format!("Unknown view: {}", name))?;
+        let target = self.state.model_bounds.map_or([0.0; 3], |b| b.center);
+        self.state.animate_to(preset.rotation(), DEFAULT_SCALE, target);
+        Ok(())
+    }
+
+    /// Set the zoom, clamped to the range the mouse wheel allows.
+    pub fn set_zoom(&mut self, scale: f32) {
+        self.state.scale = scale.clamp(ZOOM_MIN, ZOOM_MAX);
+    }
+
+    /// Center the view on a model-space point.
+    pub fn set_pan(&mut self, x: f32, y: f32, z: f32) {
+        self.state.camera_target = [x, y, z];
+    }
+
+    /// Switch between `"perspective"` and `"orthographic"` projection.
+    pub fn set_projection(&mut self, mode: &str) -> Result<(), String> {
+        self.state.projection = Projection::from_name(mode).ok_or_else(|| format!("Unknown projection: {}", mode))?;
+        Ok(())
+    }
+
+    /// Frame the whole scene: center it, move the camera to fit it and reset zoom.
+    pub fn fit_to_view(&mut self) {
+        self.state.fit_to_view();
+    }
+
+    /// Draw every object with the transparent pipeline, or none.
+    pub fn set_transparent_mode(&mut self, mode: bool) {
+        for object in &mut self.state.objects {
+            object.transparent = mode;
+        }
+    }
+
+    /// Blend transparent objects by `"sorted"` or `"weighted"` transparency.
+    pub fn set_transparency_method(&mut self, method: &str) -> Result<(), String> {
+        self.state.transparency =
+            Transparency::from_name(method).ok_or_else(|| format!("Unknown transparency method: {}", method))?;
+        Ok(())
+    }
+
+    /// Set the point cloud point diameter, in `"screen"` pixels or `"world"`
+    /// model units.
+    pub fn set_point_size(&mut self, size: f32, mode: &str) -> Result<(), String> {
+        let mode = PointSizeMode::from_name(mode).ok_or_else(|| format!("Unknown point size mode: {}", mode))?;
+        if !(size.is_finite() && size > 0.0) {
+            return Err("Point size must be a positive number".to_string());
+        }
+        self.state.point_size = size;
+        self.state.point_size_mode = mode;
+        Ok(())
+    }
+
+    /// Set polyline joins (`"round"`, `"miter"` or `"bevel"`) and caps
+    /// (`"butt"`, `"round"` or `"square"`).
+    pub fn set_line_style(&mut self, join: &str, cap: &str) -> Result<(), String> {
+        let join = LineJoin::from_name(join).ok_or_else(|| format!("Unknown line join: {}", join))?;
+        let cap = LineCap::from_name(cap).ok_or_else(|| format!("Unknown line cap: {}", cap))?;
+        self.state.line_join = join;
+        self.state.line_cap = cap;
+        Ok(())
+    }
+
+    /// Show or hide the axis lines, the `"ground"`, `"box"` or `"none"` grid,
+    /// the tick marks and the tick labels (see `set_axes` in the browser API).
+    pub fn set_axes(&mut self, axes: bool, grid: &str, ticks: bool, labels: bool) -> Result<(), String> {
+        let settings = &mut self.state.axes;
+        settings.grid = GridMode::from_name(grid).ok_or_else(|| format!("Unknown grid: {}", grid))?;
+        settings.axes = axes;
+        settings.ticks = ticks;
+        settings.labels = labels;
+        Ok(())
+    }
+
+    /// Style the axes; colors are flat rgb or rgba values, empty for the default.
+    pub fn set_axes_style(
+        &mut self,
+        axis_width: f32,
+        grid_width: f32,
+        label_size: f32,
+        axis_colors: &[f32],
+        grid_color: &[f32],
+        label_color: &[f32],
+    ) -> Result<(), String> {
+        let style = axes::parse_style(axis_width, grid_width, label_size, axis_colors, grid_color, label_color)?;
+        self.state.axes.style = style;
+        Ok(())
+    }
+
+    /// Leave the background transparent instead of the viewer's color.
+    pub fn set_transparent_background(&mut self, transparent: bool) {
+        self.transparent_background = transparent;
+    }
+
+    /// Render a frame and return its pixels: rows top to bottom, 4 bytes
+    /// (sRGB red, green, blue and straight alpha) per pixel.
+    pub fn render(&mut self) -> Result<Vec<u8>, String> {
+        let device = &self.gpu.device;
+        let view = self.texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let background =
+            if self.transparent_background { wgpu::Color::TRANSPARENT } else { renderer::BACKGROUND_COLOR };
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Headless Render Encoder"),
+        });
+        self.scene.draw(&mut encoder, &self.state, &mut self.model, &mut self.target, &view, background);
+        let readback = Readback::new(device, &mut encoder, &self.texture)?;
+        self.scene.queue.submit(std::iter::once(encoder.finish()));
+
+        let (sender, receiver) = mpsc::channel();
+        readback.buffer.slice(..).map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        device.poll(wgpu::Maintain::Wait);
+        receiver
+            .recv()
+            .map_err(|e| e.to_string())?
+            .map_err(|e| format!("Failed to read back frame: {}", e))?;
+        Ok(readback.rgba(self.transparent_background))
+    }
+
+    /// Render a frame and encode it as a PNG file's bytes.
+    pub fn render_png(&mut self) -> Result<Vec<u8>, String> {
+        let pixels = self.render()?;
+        Ok(png::encode_rgba(self.target.width, self.target.height, &pixels))
+    }
+
+    /// Render a frame and write it to `path` as a PNG.
+    pub fn save_png(&mut self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        let bytes = self.render_png()?;
+        std::fs::write(path, bytes).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// The scene, for loading models into.
+    fn scene_upload(&mut self) -> SceneUpload<'_> {
+        SceneUpload { gpu: &self.gpu, model: &mut self.model, state: &mut self.state }
+    }
+}
+
+/// Check an image size is between 1 pixel and the device's texture limit.
+fn check_size(device: &wgpu::Device, width: u32, height: u32) -> Result<(), String> {
+    let max_dimension = device.limits().max_texture_dimension_2d;
+    if width == 0 || height == 0 || width > max_dimension || height > max_dimension {
+        return Err(format!("Image size must be between 1 and {} pixels", max_dimension));
+    }
+    Ok(())
+}
+
+/// Create the texture frames resolve to, and the attachments they are drawn in.
+fn create_offscreen_target(
+    device: &wgpu::Device,
+    width: u32,
+    height: u32,
+    format: wgpu::TextureFormat,
+) -> (wgpu::Texture, RenderTarget) {
+    (
+        renderer::create_offscreen_texture(device, width, height, format),
+        renderer::create_render_target(device, width, height, format),
+    )
+}
+
+/// Run a future to completion on this thread. wgpu's native futures are
+/// ready as soon as they are polled, so this doesn't spin in practice.
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = std::pin::pin!(future);
+    let mut context = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+        std::thread::yield_now();
+    }
+}
@@ -4,11 +4,11 @@ use std::cell::RefCell;
 use std::rc::Rc;
 use wasm_bindgen::prelude::*;
 
+use crate::gizmo::pick_axis;
 use crate::picking::pick_face;
 use crate::state::{InteractionState, ZOOM_MAX, ZOOM_MIN};
 
 // Input sensitivity constants
-const MOUSE_SENSITIVITY: f32 = 0.01;
 const ZOOM_SPEED: f32 = 0.001;
 
 pub fn setup_mouse_handlers(canvas: &web_sys::HtmlCanvasElement, state: Rc<RefCell<InteractionState>>) {
@@ -16,12 +16,15 @@ pub fn setup_mouse_handlers(canvas: &web_sys::HtmlCanvasElement, state: Rc<RefCe
     {
         let state = state.clone();
         let closure = Closure::<dyn FnMut(_)>::new(move |event: web_sys::MouseEvent| {
+            let (x, y) = (event.offset_x() as f32, event.offset_y() as f32);
             let mut state = state.borrow_mut();
-            state.is_dragging = true;
-            state.drag_start_x = event.offset_x() as f32;
-            state.drag_start_y = event.offset_y() as f32;
-            state.initial_rotation_x = state.rotation_x;
-            state.initial_rotation_y = state.rotation_y;
+            // Middle and right buttons pan, as does shift + left
+            if event.button() == 1 || event.button() == 2 || event.shift_key() {
+                event.prevent_default();
+                state.begin_pan(x, y);
+            } else if event.button() == 0 {
+                state.begin_rotation(x, y);
+            }
         });
         canvas
             .add_event_listener_with_callback("mousedown", closure.as_ref().unchecked_ref())
@@ -34,11 +37,10 @@ pub fn setup_mouse_handlers(canvas: &web_sys::HtmlCanvasElement, state: Rc<RefCe
         let state = state.clone();
         let closure = Closure::<dyn FnMut(_)>::new(move |event: web_sys::MouseEvent| {
             let mut state = state.borrow_mut();
-            if state.is_dragging {
-                let dx = (event.offset_x() as f32 - state.drag_start_x) * MOUSE_SENSITIVITY;
-                let dy = (event.offset_y() as f32 - state.drag_start_y) * MOUSE_SENSITIVITY;
-                state.rotation_y = state.initial_rotation_y + dx;
-                state.rotation_x = state.initial_rotation_x + dy;
+            if state.is_panning {
+                state.drag_pan(event.offset_x() as f32, event.offset_y() as f32);
+            } else if state.is_dragging {
+                state.drag_rotation(event.offset_x() as f32, event.offset_y() as f32);
             }
         });
         canvas
@@ -47,33 +49,37 @@ pub fn setup_mouse_handlers(canvas: &web_sys::HtmlCanvasElement, state: Rc<RefCe
         closure.forget();
     }
 
-    // Mouse up - also handles click detection for face picking
+    // Mouse up - also handles click detection for face picking and the gizmo
     {
         let state = state.clone();
         let closure = Closure::<dyn FnMut(_)>::new(move |event: web_sys::MouseEvent| {
             let x = event.offset_x() as f32;
             let y = event.offset_y() as f32;
 
-            let (is_click, face) = {
+            let (is_click, view, hit) = {
                 let state = state.borrow();
                 // Check if this was a click (minimal movement)
                 let dx = x - state.drag_start_x;
                 let dy = y - state.drag_start_y;
                 let distance = (dx * dx + dy * dy).sqrt();
 
-                if distance < 5.0 {
-                    // This is a click - pick face
-                    (true, pick_face(x, y, &state))
+                if distance < 5.0 && state.is_dragging {
+                    // This is a click - on a gizmo axis, or else pick face
+                    let view = pick_axis(x, y, &state);
+                    (true, view, view.is_none().then(|| pick_face(x, y, &state)).flatten())
                 } else {
-                    (false, -1)
+                    (false, None, None)
                 }
             };
 
             let mut state = state.borrow_mut();
-            if is_click {
-                state.selected_face = face;
+            if let Some(view) = view {
+                state.turn_to(view);
+            } else if is_click {
+                state.select(hit);
             }
             state.is_dragging = false;
+            state.is_panning = false;
         });
         canvas
             .add_event_listener_with_callback("mouseup", closure.as_ref().unchecked_ref())
@@ -84,13 +90,26 @@ pub fn setup_mouse_handlers(canvas: &web_sys::HtmlCanvasElement, state: Rc<RefCe
     // Mouse leave
     {
         let closure = Closure::<dyn FnMut(_)>::new(move |_event: web_sys::MouseEvent| {
-            state.borrow_mut().is_dragging = false;
+            let mut state = state.borrow_mut();
+            state.is_dragging = false;
+            state.is_panning = false;
         });
         canvas
             .add_event_listener_with_callback("mouseleave", closure.as_ref().unchecked_ref())
             .unwrap();
         closure.forget();
     }
+
+    // Context menu - suppressed so right-drag can pan
+    {
+        let closure = Closure::<dyn FnMut(_)>::new(move |event: web_sys::MouseEvent| {
+            event.prevent_default();
+        });
+        canvas
+            .add_event_listener_with_callback("contextmenu", closure.as_ref().unchecked_ref())
+            .unwrap();
+        closure.forget();
+    }
 }
 
 pub fn setup_wheel_handler(canvas: &web_sys::HtmlCanvasElement, state: Rc<RefCell<InteractionState>>) {
@@ -100,6 +119,7 @@ pub fn setup_wheel_handler(canvas: &web_sys::HtmlCanvasElement, state: Rc<RefCel
         let mut state = state.borrow_mut();
         let delta = event.delta_y() as f32;
         let zoom_factor = 1.0 - delta * ZOOM_SPEED;
+        state.stop_camera_animation();
         state.scale = (state.scale * zoom_factor).clamp(ZOOM_MIN, ZOOM_MAX);
     });
 
@@ -116,6 +136,20 @@ pub fn setup_wheel_handler(canvas: &web_sys::HtmlCanvasElement, state: Rc<RefCel
     closure.forget();
 }
 
+/// Touch position relative to the canvas, in CSS pixels.
+fn touch_position(canvas: &web_sys::HtmlCanvasElement, touch: &web_sys::Touch) -> (f32, f32) {
+    let rect = canvas.get_bounding_client_rect();
+    (touch.client_x() as f32 - rect.left() as f32, touch.client_y() as f32 - rect.top() as f32)
+}
+
+/// Midpoint of the first two touches relative to the canvas, in CSS pixels.
+fn touch_midpoint(canvas: &web_sys::HtmlCanvasElement, event: &web_sys::TouchEvent) -> Option<(f32, f32)> {
+    let touches = event.touches();
+    let (x0, y0) = touch_position(canvas, &touches.get(0)?);
+    let (x1, y1) = touch_position(canvas, &touches.get(1)?);
+    Some(((x0 + x1) * 0.5, (y0 + y1) * 0.5))
+}
+
 fn get_pinch_distance(event: &web_sys::TouchEvent) -> Option<f32> {
     let touches = event.touches();
     if touches.length() >= 2 {
@@ -136,6 +170,7 @@ pub fn setup_touch_handlers(canvas: &web_sys::HtmlCanvasElement, state: Rc<RefCe
     // Touch start
     {
         let state = state.clone();
+        let canvas_clone = canvas.clone();
         let closure = Closure::<dyn FnMut(_)>::new(move |event: web_sys::TouchEvent| {
             event.prevent_default();
             let touches = event.touches();
@@ -144,21 +179,22 @@ pub fn setup_touch_handlers(canvas: &web_sys::HtmlCanvasElement, state: Rc<RefCe
             if touches.length() == 1 {
                 // Single finger - start rotation
                 if let Some(touch) = touches.get(0) {
-                    state.is_dragging = true;
+                    let (x, y) = touch_position(&canvas_clone, &touch);
                     state.is_pinching = false;
-                    state.drag_start_x = touch.client_x() as f32;
-                    state.drag_start_y = touch.client_y() as f32;
-                    state.initial_rotation_x = state.rotation_x;
-                    state.initial_rotation_y = state.rotation_y;
+                    state.begin_rotation(x, y);
                 }
             } else if touches.length() >= 2 {
-                // Two fingers - start pinch zoom
+                // Two fingers - start pinch zoom and pan
                 state.is_dragging = false;
                 state.is_pinching = true;
+                state.stop_camera_animation();
                 if let Some(dist) = get_pinch_distance(&event) {
                     state.initial_pinch_distance = dist;
                     state.initial_scale = state.scale;
                 }
+                if let Some((x, y)) = touch_midpoint(&canvas_clone, &event) {
+                    state.begin_pan(x, y);
+                }
             }
         });
         canvas
@@ -174,26 +210,28 @@ pub fn setup_touch_handlers(canvas: &web_sys::HtmlCanvasElement, state: Rc<RefCe
     // Touch move
     {
         let state = state.clone();
+        let canvas_clone = canvas.clone();
         let closure = Closure::<dyn FnMut(_)>::new(move |event: web_sys::TouchEvent| {
             event.prevent_default();
             let touches = event.touches();
             let mut state = state.borrow_mut();
 
             if state.is_pinching && touches.length() >= 2 {
-                // Pinch zoom
+                // Pinch zoom and two-finger pan
                 if let Some(dist) = get_pinch_distance(&event) {
                     if state.initial_pinch_distance > 0.0 {
                         let scale_factor = dist / state.initial_pinch_distance;
                         state.scale = (state.initial_scale * scale_factor).clamp(ZOOM_MIN, ZOOM_MAX);
                     }
                 }
+                if let Some((x, y)) = touch_midpoint(&canvas_clone, &event) {
+                    state.drag_pan(x, y);
+                }
             } else if state.is_dragging && touches.length() == 1 {
                 // Single finger rotation
                 if let Some(touch) = touches.get(0) {
-                    let dx = (touch.client_x() as f32 - state.drag_start_x) * MOUSE_SENSITIVITY;
-                    let dy = (touch.client_y() as f32 - state.drag_start_y) * MOUSE_SENSITIVITY;
-                    state.rotation_y = state.initial_rotation_y + dx;
-                    state.rotation_x = state.initial_rotation_x + dy;
+                    let (x, y) = touch_position(&canvas_clone, &touch);
+                    state.drag_rotation(x, y);
                 }
             }
         });
@@ -207,7 +245,7 @@ pub fn setup_touch_handlers(canvas: &web_sys::HtmlCanvasElement, state: Rc<RefCe
         closure.forget();
     }
 
-    // Touch end - also handles tap detection for face picking
+    // Touch end - also handles tap detection for face picking and the gizmo
     {
         let state = state.clone();
         let canvas_clone = canvas.clone();
@@ -219,12 +257,9 @@ pub fn setup_touch_handlers(canvas: &web_sys::HtmlCanvasElement, state: Rc<RefCe
             if touches.length() == 0 && changed_touches.length() == 1 {
                 // Single finger lifted - check if it was a tap
                 if let Some(touch) = changed_touches.get(0) {
-                    // Get touch position relative to canvas
-                    let rect = canvas_clone.get_bounding_client_rect();
-                    let x = touch.client_x() as f32 - rect.left() as f32;
-                    let y = touch.client_y() as f32 - rect.top() as f32;
+                    let (x, y) = touch_position(&canvas_clone, &touch);
 
-                    let (is_tap, face) = {
+                    let (is_tap, view, hit) = {
                         let state = state.borrow();
                         // Check if this was a tap (minimal movement from start)
                         let dx = x - state.drag_start_x;
@@ -232,18 +267,22 @@ pub fn setup_touch_handlers(canvas: &web_sys::HtmlCanvasElement, state: Rc<RefCe
                         let distance = (dx * dx + dy * dy).sqrt();
 
                         if distance < 10.0 && !state.is_pinching {
-                            (true, pick_face(x, y, &state))
+                            let view = pick_axis(x, y, &state);
+                            (true, view, view.is_none().then(|| pick_face(x, y, &state)).flatten())
                         } else {
-                            (false, -1)
+                            (false, None, None)
                         }
                     };
 
                     let mut state = state.borrow_mut();
-                    if is_tap {
-                        state.selected_face = face;
+                    if let Some(view) = view {
+                        state.turn_to(view);
+                    } else if is_tap {
+                        state.select(hit);
                     }
                     state.is_dragging = false;
                     state.is_pinching = false;
+                    state.is_panning = false;
                     return;
                 }
             }
@@ -253,15 +292,14 @@ pub fn setup_touch_handlers(canvas: &web_sys::HtmlCanvasElement, state: Rc<RefCe
                 // All fingers lifted
                 state.is_dragging = false;
                 state.is_pinching = false;
+                state.is_panning = false;
             } else if touches.length() == 1 && state.is_pinching {
                 // Went from pinch to single finger - start rotation from current position
                 if let Some(touch) = touches.get(0) {
+                    let (x, y) = touch_position(&canvas_clone, &touch);
                     state.is_pinching = false;
-                    state.is_dragging = true;
-                    state.drag_start_x = touch.client_x() as f32;
-                    state.drag_start_y = touch.client_y() as f32;
-                    state.initial_rotation_x = state.rotation_x;
-                    state.initial_rotation_y = state.rotation_y;
+                    state.is_panning = false;
+                    state.begin_rotation(x, y);
                 }
             }
         });
@@ -282,6 +320,7 @@ pub fn setup_touch_handlers(canvas: &web_sys::HtmlCanvasElement, state: Rc<RefCe
             let mut state = state.borrow_mut();
             state.is_dragging = false;
             state.is_pinching = false;
+            state.is_panning = false;
         });
         canvas
             .add_event_listener_with_callback_and_add_event_listener_options(
@@ -319,3 +358,20 @@ pub fn setup_keyboard_handler(window: &web_sys::Window, debug_panel: &web_sys::H
         .unwrap();
     closure.forget();
 }
+
+/// Keep the surface in sync with the canvas's displayed size.
+pub fn setup_resize_observer(canvas: &web_sys::HtmlCanvasElement, window: &web_sys::Window) {
+    let canvas_clone = canvas.clone();
+    let window = window.clone();
+    let closure = Closure::<dyn FnMut(_)>::new(move |_entries: js_sys::Array| {
+        let width = canvas_clone.client_width() as u32;
+        let height = canvas_clone.client_height() as u32;
+        if width > 0 && height > 0 {
+            crate::resize(width, height, window.device_pixel_ratio());
+        }
+    });
+    let observer = web_sys::ResizeObserver::new(closure.as_ref().unchecked_ref())
+        .expect("Failed to create ResizeObserver");
+    observer.observe(canvas);
+    closure.forget();
+}
@@ -5,29 +5,54 @@
 //! - `vertex` - Vertex types and GPU buffer layouts
 //! - `state` - Application state and global state management
 //! - `model` - Model parsing and geometry utilities
-//! - `picking` - Ray-triangle intersection and face picking
+//! - `gltf` - glTF 2.0 / GLB scene import
+//! - `picking` - Ray casting for face, line and point picking
+//! - `sorting` - Back-to-front ordering of transparent geometry
+//! - `axes` - Axis, tick and grid geometry with nice-number ticks
+//! - `glyphs` - Bitmap font glyph atlas and text layout for labels
+//! - `gizmo` - Orientation gizmo geometry and axis picking
 //! - `input` - Event handlers for mouse, touch, wheel, keyboard
 //! - `renderer` - WebGPU pipeline creation and render loop
-
+//! - `upload` - Model upload into the scene, shared by the viewer and headless rendering
+//! - `headless` - Native offscreen rendering to RGBA or PNG (not on wasm)
+//! - `png` - Minimal PNG encoder
+
+mod axes;
+mod gizmo;
+mod gltf;
+mod glyphs;
+#[cfg(not(target_arch = "wasm32"))]
+mod headless;
 mod input;
 mod math;
 mod model;
 mod picking;
+mod png;
 mod renderer;
+mod sorting;
 mod state;
+mod upload;
 mod vertex;
 
+#[cfg(not(target_arch = "wasm32"))]
+pub use headless::HeadlessRenderer;
+
 use std::cell::RefCell;
 use std::rc::Rc;
 use wasm_bindgen::prelude::*;
-use wgpu::util::DeviceExt;
 
-use model::{extract_triangles, parse_model};
-use renderer::RenderContext;
+use math::{quat_from_euler_xy, quat_normalize, quat_to_euler_xy, Mat4};
+use model::{
+    parse_model, parse_model_binary, parse_obj, parse_ply, parse_stl, point_vertices, polylines, ModelError, SharedMesh,
+    DEFAULT_COLOR,
+};
+use renderer::{RenderContext, SceneRenderer};
 use state::{
-    GpuResources, InteractionState, ModelResources, DEFAULT_ROTATION_X, DEFAULT_ROTATION_Y,
-    DEFAULT_SCALE, GPU_RESOURCES, INTERACTION_STATE, MODEL_RESOURCES,
+    default_rotation, GpuResources, GridMode, InteractionState, LineCap, LineJoin, ModelResources, PointSizeMode,
+    Projection, RotationMode, SceneObject, SurfaceResources, Transparency, ViewPreset, DEFAULT_SCALE, GPU_RESOURCES,
+    INTERACTION_STATE, MODEL_RESOURCES, SCENE_RENDERER, SURFACE_RESOURCES, ZOOM_MAX, ZOOM_MIN,
 };
+use upload::{SceneUpload, MAIN_OBJECT_ID};
 
 // Built-in models (embedded at compile time)
 const CUBE_MODEL: &str = include_str!("../models/cube.txt");
@@ -41,7 +66,9 @@ const PYRAMID_MODEL: &str = include_str!("../models/pyramid.txt");
 pub fn reset_zoom() {
     INTERACTION_STATE.with(|state| {
         if let Some(state) = state.borrow().as_ref() {
-            state.borrow_mut().scale = DEFAULT_SCALE;
+            let mut s = state.borrow_mut();
+            s.stop_camera_animation();
+            s.scale = DEFAULT_SCALE;
         }
     });
 }
@@ -51,34 +78,164 @@ pub fn reset_rotation() {
     INTERACTION_STATE.with(|state| {
         if let Some(state) = state.borrow().as_ref() {
             let mut s = state.borrow_mut();
-            s.rotation_x = DEFAULT_ROTATION_X;
-            s.rotation_y = DEFAULT_ROTATION_Y;
+            s.stop_camera_animation();
+            s.rotation = default_rotation();
         }
     });
 }
 
+/// Rotation as `[x, y]` Euler angles in radians (about X, then Y).
+///
+/// Any roll from arcball dragging is dropped; use `get_rotation_quaternion`
+/// to save and restore a view exactly.
 #[wasm_bindgen]
 pub fn get_rotation() -> js_sys::Float32Array {
+    let arr = js_sys::Float32Array::new_with_length(2);
+    INTERACTION_STATE.with(|state| {
+        if let Some(state) = state.borrow().as_ref() {
+            let (x, y) = quat_to_euler_xy(state.borrow().rotation);
+            arr.set_index(0, x);
+            arr.set_index(1, y);
+        }
+    });
+    arr
+}
+
+/// Rotation as a unit quaternion `[x, y, z, w]`.
+#[wasm_bindgen]
+pub fn get_rotation_quaternion() -> js_sys::Float32Array {
+    INTERACTION_STATE.with(|state| {
+        let rotation = state.borrow().as_ref().map_or(default_rotation(), |s| s.borrow().rotation);
+        js_sys::Float32Array::from(&rotation[..])
+    })
+}
+
+/// Set the rotation from `x`, `y` Euler angles in radians, or from a
+/// quaternion `[x, y, z, w]` when all four components are given.
+#[wasm_bindgen]
+pub fn set_rotation(x: f32, y: f32, z: Option<f32>, w: Option<f32>) {
+    let rotation = match (z, w) {
+        (Some(z), Some(w)) => quat_normalize([x, y, z, w]),
+        _ => quat_from_euler_xy(x, y),
+    };
+    INTERACTION_STATE.with(|state| {
+        if let Some(state) = state.borrow().as_ref() {
+            let mut s = state.borrow_mut();
+            s.stop_camera_animation();
+            s.rotation = rotation;
+        }
+    });
+}
+
+/// Choose how dragging rotates the model: `"arcball"` or `"turntable"`.
+#[wasm_bindgen]
+pub fn set_rotation_mode(mode: &str) -> Result<(), JsValue> {
+    let mode = match mode {
+        "arcball" => RotationMode::Arcball,
+        "turntable" => RotationMode::Turntable,
+        _ => return Err(JsValue::from_str(&format!("Unknown rotation mode: {}", mode))),
+    };
+    INTERACTION_STATE.with(|state| {
+        if let Some(state) = state.borrow().as_ref() {
+            state.borrow_mut().rotation_mode = mode;
+        }
+    });
+    Ok(())
+}
+
+/// Switch between `"perspective"` and `"orthographic"` projection.
+#[wasm_bindgen]
+pub fn set_projection(mode: &str) -> Result<(), JsValue> {
+    let projection =
+        Projection::from_name(mode).ok_or_else(|| JsValue::from_str(&format!("Unknown projection: {}", mode)))?;
     INTERACTION_STATE.with(|state| {
         if let Some(state) = state.borrow().as_ref() {
-            let s = state.borrow();
-            let arr = js_sys::Float32Array::new_with_length(2);
-            arr.set_index(0, s.rotation_x);
-            arr.set_index(1, s.rotation_y);
-            arr
-        } else {
-            js_sys::Float32Array::new_with_length(2)
+            state.borrow_mut().projection = projection;
+        }
+    });
+    Ok(())
+}
+
+/// Current projection: `"perspective"` or `"orthographic"`.
+#[wasm_bindgen]
+pub fn get_projection() -> String {
+    INTERACTION_STATE.with(|state| {
+        let projection = state.borrow().as_ref().map_or(Projection::Perspective, |s| s.borrow().projection);
+        match projection {
+            Projection::Perspective => "perspective",
+            Projection::Orthographic => "orthographic",
         }
+        .to_string()
     })
 }
 
+/// Turn the camera to a standard view: `"front"`, `"back"`, `"left"`,
+/// `"right"`, `"top"`, `"bottom"` or `"isometric"`.
+///
+/// The model is re-centered at default zoom, animating over the duration set
+/// by `set_view_transition_duration`.
+#[wasm_bindgen]
+pub fn set_view(name: &str) -> Result<(), JsValue> {
+    let preset = ViewPreset::from_name(name).ok_or_else(|| JsValue::from_str(&format!("Unknown view: {}", name)))?;
+    INTERACTION_STATE.with(|state| {
+        if let Some(state) = state.borrow().as_ref() {
+            let mut s = state.borrow_mut();
+            let target = s.model_bounds.map_or([0.0; 3], |b| b.center);
+            s.animate_to(preset.rotation(), DEFAULT_SCALE, target);
+        }
+    });
+    Ok(())
+}
+
+/// How long `set_view` transitions take, in milliseconds (0 to jump).
+#[wasm_bindgen]
+pub fn set_view_transition_duration(ms: f64) {
+    INTERACTION_STATE.with(|state| {
+        if let Some(state) = state.borrow().as_ref() {
+            state.borrow_mut().view_transition_ms = ms.max(0.0);
+        }
+    });
+}
+
+/// Re-center the view on the model's bounding box.
 #[wasm_bindgen]
-pub fn set_rotation(x: f32, y: f32) {
+pub fn reset_pan() {
     INTERACTION_STATE.with(|state| {
         if let Some(state) = state.borrow().as_ref() {
             let mut s = state.borrow_mut();
-            s.rotation_x = x;
-            s.rotation_y = y;
+            s.stop_camera_animation();
+            s.camera_target = s.model_bounds.map_or([0.0; 3], |b| b.center);
+        }
+    });
+}
+
+/// Frame the whole model: center it, move the camera to fit it and reset zoom.
+#[wasm_bindgen]
+pub fn fit_to_view() {
+    INTERACTION_STATE.with(|state| {
+        if let Some(state) = state.borrow().as_ref() {
+            state.borrow_mut().fit_to_view();
+        }
+    });
+}
+
+/// Model-space point the camera is centered on, as `[x, y, z]`.
+#[wasm_bindgen]
+pub fn get_pan() -> js_sys::Float32Array {
+    INTERACTION_STATE.with(|state| {
+        let target = state.borrow().as_ref().map_or([0.0; 3], |s| s.borrow().camera_target);
+        js_sys::Float32Array::from(&target[..])
+    })
+}
+
+/// Center the view on a model-space point.
+#[wasm_bindgen]
+pub fn set_pan(x: f32, y: f32, z: f32) {
+    INTERACTION_STATE.with(|state| {
+        if let Some(state) = state.borrow().as_ref() {
+            let mut s = state.borrow_mut();
+            s.stop_camera_animation();
+            s.camera_target = [x, y, z];
         }
     });
 }
@@ -98,60 +255,326 @@ pub fn get_zoom() -> f32 {
 pub fn set_zoom(scale: f32) {
     INTERACTION_STATE.with(|state| {
         if let Some(state) = state.borrow().as_ref() {
-            state.borrow_mut().scale = scale;
+            let mut s = state.borrow_mut();
+            s.stop_camera_animation();
+            s.scale = scale.clamp(ZOOM_MIN, ZOOM_MAX);
         }
     });
 }
 
 #[wasm_bindgen]
 pub fn load_model(model_text: &str) -> Result<(), JsValue> {
-    let (vertices, indices) = parse_model(model_text).map_err(|e| JsValue::from_str(&e))?;
+    let mesh = parse_model(model_text).map_err(model_error_to_js)?;
+    replace_scene(|scene, id| scene.upload_shared_model(id, &mesh));
+    Ok(())
+}
 
-    // Extract triangles for picking
-    let model_triangles = extract_triangles(&vertices, &indices);
+/// Convert a parse error into a JS `Error` carrying `line`, `column` and `kind` properties.
+fn model_error_to_js(err: ModelError) -> JsValue {
+    let js_err = js_sys::Error::new(&err.to_string());
+    let _ = js_sys::Reflect::set(&js_err, &"line".into(), &(err.line() as u32).into());
+    let _ = js_sys::Reflect::set(&js_err, &"column".into(), &(err.column() as u32).into());
+    let _ = js_sys::Reflect::set(&js_err, &"kind".into(), &err.kind().into());
+    js_err.into()
+}
 
-    GPU_RESOURCES.with(|gpu| {
-        MODEL_RESOURCES.with(|model| {
-            let gpu = gpu.borrow();
-            let model = model.borrow();
-
-            if let (Some(gpu), Some(model)) = (gpu.as_ref(), model.as_ref()) {
-                let vertex_buffer =
-                    gpu.device
-                        .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                            label: Some("Vertex Buffer"),
-                            contents: bytemuck::cast_slice(&vertices),
-                            usage: wgpu::BufferUsages::VERTEX,
-                        });
-
-                let index_buffer =
-                    gpu.device
-                        .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                            label: Some("Index Buffer"),
-                            contents: bytemuck::cast_slice(&indices),
-                            usage: wgpu::BufferUsages::INDEX,
-                        });
-
-                let mut model = model.borrow_mut();
-                model.vertex_buffer = vertex_buffer;
-                model.index_buffer = index_buffer;
-                model.num_indices = indices.len() as u32;
-            }
-        });
+/// Load a Wavefront OBJ model, optionally colored by the materials in `mtl_text`.
+#[wasm_bindgen]
+pub fn load_obj(obj_text: &str, mtl_text: Option<String>) -> Result<(), JsValue> {
+    let (vertices, indices) =
+        parse_obj(obj_text, mtl_text.as_deref()).map_err(|e| JsValue::from_str(&e))?;
+    replace_scene(|scene, id| scene.upload_model(id, &vertices, &indices, &[]));
+    Ok(())
+}
+
+/// Load a binary or ASCII STL model from raw file bytes.
+///
+/// `color` is an optional `[r, g, b]` or `[r, g, b, a]` applied to every facet.
+#[wasm_bindgen]
+pub fn load_stl(bytes: &[u8], color: Option<Vec<f32>>) -> Result<(), JsValue> {
+    let color = match color.as_deref() {
+        None => DEFAULT_COLOR,
+        Some(&[r, g, b]) => [r, g, b, 1.0],
+        Some(&[r, g, b, a]) => [r, g, b, a],
+        Some(_) => return Err(JsValue::from_str("STL color must have 3 or 4 components")),
+    };
+    let (vertices, indices) = parse_stl(bytes, color).map_err(|e| JsValue::from_str(&e))?;
+    replace_scene(|scene, id| scene.upload_model(id, &vertices, &indices, &[]));
+    Ok(())
+}
+
+/// Load an ASCII or binary PLY mesh from raw file bytes. Files without
+/// faces are shown as point clouds.
+///
+/// If `color_by` names a non-standard scalar vertex property (e.g.
+/// `"intensity"`), vertices are colored by it through a viridis colormap
+/// instead of the file's `red/green/blue` values.
+#[wasm_bindgen]
+pub fn load_ply(bytes: &[u8], color_by: Option<String>) -> Result<(), JsValue> {
+    let ply = parse_ply(bytes).map_err(|e| JsValue::from_str(&e))?;
+    if ply.is_point_cloud() {
+        let points = ply.to_points(color_by.as_deref()).map_err(|e| JsValue::from_str(&e))?;
+        replace_scene(|scene, id| scene.upload_points(id, &points));
+        return Ok(());
+    }
+    let (vertices, indices) = ply.to_mesh(color_by.as_deref()).map_err(|e| JsValue::from_str(&e))?;
+    replace_scene(|scene, id| scene.upload_model(id, &vertices, &indices, &[]));
+    Ok(())
+}
+
+/// Load a point cloud, replacing the scene, and frame it.
+///
+/// `positions` holds xyz per point and `colors` rgb or rgba per point (or
+/// nothing, for the default gray). `sizes` optionally scales each point's
+/// diameter; see `set_point_size`. Points are drawn as round, shaded splats,
+/// and picking reports the point index in place of a face id.
+#[wasm_bindgen]
+pub fn load_points(positions: &[f32], colors: &[f32], sizes: Option<Vec<f32>>) -> Result<(), JsValue> {
+    let points = point_vertices(positions, colors, sizes.as_deref().unwrap_or(&[])).map_err(|e| JsValue::from_str(&e))?;
+    replace_scene(|scene, id| scene.upload_points(id, &points));
+    Ok(())
+}
+
+/// Set the diameter of point cloud points and what it is measured in:
+/// `"screen"` for CSS pixels (points keep their size as the view zooms) or
+/// `"world"` for model units. Each point's size factor multiplies it.
+#[wasm_bindgen]
+pub fn set_point_size(size: f32, mode: &str) -> Result<(), JsValue> {
+    let mode = PointSizeMode::from_name(mode)
+        .ok_or_else(|| JsValue::from_str(&format!("Unknown point size mode: {}", mode)))?;
+    if !(size.is_finite() && size > 0.0) {
+        return Err(JsValue::from_str("Point size must be a positive number"));
+    }
+    INTERACTION_STATE.with(|state| {
+        if let Some(state) = state.borrow().as_ref() {
+            let mut state = state.borrow_mut();
+            state.point_size = size;
+            state.point_size_mode = mode;
+        }
     });
+    Ok(())
+}
+
+/// Load polylines, replacing the scene, and frame them.
+///
+/// `positions` holds xyz per point and `colors` rgb or rgba per point (or
+/// nothing, for the default gray), blended along each segment. `lengths`
+/// splits the points into polylines of that many points each; leave it
+/// empty for a single polyline. A polyline that ends where it starts is
+/// closed. Lines are `width` CSS pixels wide, and picking reports the index
+/// of the polyline in place of a face id.
+#[wasm_bindgen]
+pub fn load_polylines(positions: &[f32], colors: &[f32], lengths: &[u32], width: f32) -> Result<(), JsValue> {
+    let lines = polylines(positions, colors, lengths, width).map_err(|e| JsValue::from_str(&e))?;
+    let mesh = SharedMesh::from_lines(lines);
+    replace_scene(|scene, id| scene.upload_shared_model(id, &mesh));
+    Ok(())
+}
 
-    // Update model triangles for picking and reset selection
+/// Set how polylines are drawn where segments meet (`"round"`, `"miter"`
+/// or `"bevel"`) and at their open ends (`"butt"`, `"round"` or `"square"`).
+#[wasm_bindgen]
+pub fn set_line_style(join: &str, cap: &str) -> Result<(), JsValue> {
+    let join = LineJoin::from_name(join).ok_or_else(|| JsValue::from_str(&format!("Unknown line join: {}", join)))?;
+    let cap = LineCap::from_name(cap).ok_or_else(|| JsValue::from_str(&format!("Unknown line cap: {}", cap)))?;
     INTERACTION_STATE.with(|state| {
         if let Some(state) = state.borrow().as_ref() {
             let mut state = state.borrow_mut();
-            state.model_triangles = model_triangles;
-            state.selected_face = -1;
+            state.line_join = join;
+            state.line_cap = cap;
         }
     });
+    Ok(())
+}
 
+/// Show or hide the parts of the axes drawn around the scene's bounds:
+/// X, Y and Z axis lines with their names, a `"ground"` or `"box"` grid (or
+/// `"none"`), tick marks at round values, and the tick values as labels.
+/// All are hidden until turned on.
+#[wasm_bindgen]
+pub fn set_axes(axes: bool, grid: &str, ticks: bool, labels: bool) -> Result<(), JsValue> {
+    let grid = GridMode::from_name(grid).ok_or_else(|| JsValue::from_str(&format!("Unknown grid: {}", grid)))?;
+    INTERACTION_STATE.with(|state| {
+        if let Some(state) = state.borrow().as_ref() {
+            let settings = &mut state.borrow_mut().axes;
+            settings.axes = axes;
+            settings.grid = grid;
+            settings.ticks = ticks;
+            settings.labels = labels;
+        }
+    });
+    Ok(())
+}
+
+/// Style the axes: line widths and label height in CSS pixels, the X, Y and
+/// Z axis colors (three rgb or rgba colors, one after another), and the grid
+/// and label colors (rgb or rgba). An empty color array keeps the default.
+#[wasm_bindgen]
+pub fn set_axes_style(
+    axis_width: f32,
+    grid_width: f32,
+    label_size: f32,
+    axis_colors: &[f32],
+    grid_color: &[f32],
+    label_color: &[f32],
+) -> Result<(), JsValue> {
+    let style = axes::parse_style(axis_width, grid_width, label_size, axis_colors, grid_color, label_color)
+        .map_err(|e| JsValue::from_str(&e))?;
+    INTERACTION_STATE.with(|state| {
+        if let Some(state) = state.borrow().as_ref() {
+            state.borrow_mut().axes.style = style;
+        }
+    });
+    Ok(())
+}
+
+/// Show or hide the orientation gizmo in the canvas's bottom-right corner.
+/// Clicking one of its axes turns the view to look down that axis.
+#[wasm_bindgen]
+pub fn set_gizmo_visible(visible: bool) {
+    INTERACTION_STATE.with(|state| {
+        if let Some(state) = state.borrow().as_ref() {
+            state.borrow_mut().show_gizmo = visible;
+        }
+    });
+}
+
+/// Load a glTF 2.0 scene from a `.gltf` (JSON) or `.glb` file's bytes.
+///
+/// Buffers must be embedded (data URIs or the GLB binary chunk).
+#[wasm_bindgen]
+pub fn load_gltf(bytes: &[u8]) -> Result<(), JsValue> {
+    let (vertices, indices) = gltf::parse_gltf(bytes).map_err(|e| JsValue::from_str(&e))?;
+    replace_scene(|scene, id| scene.upload_model(id, &vertices, &indices, &[]));
+    Ok(())
+}
+
+/// Load a model in the compact binary format (see `model::parse_model_binary`).
+///
+/// Vertex and index data go to the GPU straight from `bytes`; they are only
+/// copied once up front if the buffer handed over from JS is not 4-byte aligned.
+#[wasm_bindgen]
+pub fn load_model_binary(bytes: &[u8]) -> Result<(), JsValue> {
+    let mut aligned: Vec<u32>;
+    let bytes = if bytes.as_ptr().align_offset(4) == 0 {
+        bytes
+    } else {
+        aligned = vec![0; bytes.len().div_ceil(4)];
+        bytemuck::cast_slice_mut(&mut aligned)[..bytes.len()].copy_from_slice(bytes);
+        &bytemuck::cast_slice(&aligned)[..bytes.len()]
+    };
+    let mesh = parse_model_binary(bytes).map_err(|e| JsValue::from_str(&e))?;
+    replace_scene(|scene, id| scene.upload_shared_model(id, &mesh));
     Ok(())
 }
 
+/// Clear the scene, add one object as `MAIN_OBJECT_ID` with `upload`, and frame it.
+fn replace_scene(upload: impl FnOnce(&mut SceneUpload, u32)) {
+    with_scene_upload(|scene| scene.replace_scene(upload));
+}
+
+/// Run `upload` on the viewer's scene, once `init` has set it up.
+fn with_scene_upload(upload: impl FnOnce(&mut SceneUpload)) {
+    GPU_RESOURCES.with(|gpu| {
+        MODEL_RESOURCES.with(|model| {
+            INTERACTION_STATE.with(|state| {
+                let (gpu, model, state) = (gpu.borrow(), model.borrow(), state.borrow());
+                if let (Some(gpu), Some(model), Some(state)) = (gpu.as_ref(), model.as_ref(), state.as_ref()) {
+                    upload(&mut SceneUpload { gpu, model: &mut model.borrow_mut(), state: &mut state.borrow_mut() });
+                }
+            });
+        });
+    });
+}
+
+/// Add a model in the text format to the scene next to the objects already
+/// there, returning its id.
+///
+/// Pass `id` to choose the id (e.g. to mirror a scene kept elsewhere); an
+/// object that already has it gets the new geometry. The camera does not
+/// move; call `fit_to_view` to frame the whole scene.
+#[wasm_bindgen]
+pub fn add_object(model_text: &str, id: Option<u32>) -> Result<u32, JsValue> {
+    let mesh = parse_model(model_text).map_err(model_error_to_js)?;
+    let id = id.unwrap_or_else(|| {
+        INTERACTION_STATE.with(|state| state.borrow().as_ref().map_or(MAIN_OBJECT_ID, |s| s.borrow().next_object_id))
+    });
+    with_scene_upload(|scene| scene.upload_shared_model(id, &mesh));
+    Ok(id)
+}
+
+/// Remove an object and its GPU buffers from the scene.
+#[wasm_bindgen]
+pub fn remove_object(id: u32) -> Result<(), JsValue> {
+    MODEL_RESOURCES.with(|model| {
+        if let Some(model) = model.borrow().as_ref() {
+            model.borrow_mut().objects.retain(|object| object.id != id);
+        }
+    });
+    INTERACTION_STATE.with(|state| {
+        let state = state.borrow();
+        let mut state = state.as_ref().ok_or_else(|| unknown_object(id))?.borrow_mut();
+        let index = state.objects.iter().position(|object| object.id == id).ok_or_else(|| unknown_object(id))?;
+        state.objects.remove(index);
+        if state.selected_object == Some(id) {
+            state.select(None);
+        }
+        state.update_scene_bounds();
+        Ok(())
+    })
+}
+
+/// Set an object's transform from 16 numbers in column-major order (the
+/// layout of a glTF `matrix` or a WebGPU `mat4x4`).
+#[wasm_bindgen]
+pub fn set_object_transform(id: u32, matrix: Vec<f32>) -> Result<(), JsValue> {
+    let matrix: [f32; 16] = matrix
+        .try_into()
+        .map_err(|_| JsValue::from_str("Object transform must have 16 components"))?;
+    let transform: Mat4 = std::array::from_fn(|col| std::array::from_fn(|row| matrix[col * 4 + row]));
+    update_object(id, |object| object.transform = transform)
+}
+
+/// Show or hide an object. Hidden objects are neither drawn nor picked.
+#[wasm_bindgen]
+pub fn set_object_visible(id: u32, visible: bool) -> Result<(), JsValue> {
+    update_object(id, |object| object.visible = visible)
+}
+
+/// Draw an object with the transparent pipeline (see `set_transparent_mode`).
+#[wasm_bindgen]
+pub fn set_object_transparent(id: u32, transparent: bool) -> Result<(), JsValue> {
+    update_object(id, |object| object.transparent = transparent)
+}
+
+/// The last picked face as `[object id, face id]` (the polyline id for a
+/// line, the point index for a point cloud), or `[-1, -1]` if none.
+#[wasm_bindgen]
+pub fn get_selection() -> js_sys::Int32Array {
+    INTERACTION_STATE.with(|state| {
+        let selection = state.borrow().as_ref().map_or([-1, -1], |s| {
+            let s = s.borrow();
+            [s.selected_object.map_or(-1, |id| id as i32), s.selected_face]
+        });
+        js_sys::Int32Array::from(&selection[..])
+    })
+}
+
+/// Apply `update` to object `id` and refresh the scene bounds.
+fn update_object(id: u32, update: impl FnOnce(&mut SceneObject)) -> Result<(), JsValue> {
+    INTERACTION_STATE.with(|state| {
+        let state = state.borrow();
+        let mut state = state.as_ref().ok_or_else(|| unknown_object(id))?.borrow_mut();
+        update(state.object_mut(id).ok_or_else(|| unknown_object(id))?);
+        state.update_scene_bounds();
+        Ok(())
+    })
+}
+
+fn unknown_object(id: u32) -> JsValue {
+    JsValue::from_str(&format!("Unknown object: {}", id))
+}
+
 #[wasm_bindgen]
 pub fn load_cube_model() -> Result<(), JsValue> {
     load_model(CUBE_MODEL)
@@ -162,18 +585,123 @@ pub fn load_pyramid_model() -> Result<(), JsValue> {
     load_model(PYRAMID_MODEL)
 }
 
-/// Enable or disable the transparent render pipeline.
+/// Enable or disable the transparent render pipeline for every object in the scene.
 ///
-/// When `true`, geometry is drawn with depth writes disabled so that
-/// back-to-front sorted transparent voxels composite correctly through each
-/// other.  Set to `false` for opaque models (cube, pyramid, isosurfaces).
+/// When `true`, geometry is drawn with depth writes disabled, its triangles
+/// re-sorted back-to-front whenever the view rotates, so that transparent
+/// voxels composite correctly through each other.  Set to `false` for opaque
+/// models (cube, pyramid, isosurfaces).
+/// Use `set_object_transparent` to change a single object.
 #[wasm_bindgen]
 pub fn set_transparent_mode(mode: bool) {
-    MODEL_RESOURCES.with(|model| {
-        if let Some(model) = model.borrow().as_ref() {
-            model.borrow_mut().transparent_mode = mode;
+    INTERACTION_STATE.with(|state| {
+        if let Some(state) = state.borrow().as_ref() {
+            for object in &mut state.borrow_mut().objects {
+                object.transparent = mode;
+            }
+        }
+    });
+}
+
+/// Choose how transparent objects are blended: `"sorted"` (alpha blending,
+/// re-sorted back to front as the view rotates) or `"weighted"` (weighted
+/// blended order-independent transparency, for intersecting or nested
+/// transparent surfaces that no triangle order can draw correctly).
+#[wasm_bindgen]
+pub fn set_transparency_method(method: &str) -> Result<(), JsValue> {
+    let transparency = Transparency::from_name(method)
+        .ok_or_else(|| JsValue::from_str(&format!("Unknown transparency method: {}", method)))?;
+    INTERACTION_STATE.with(|state| {
+        if let Some(state) = state.borrow().as_ref() {
+            state.borrow_mut().transparency = transparency;
         }
     });
+    Ok(())
+}
+
+/// Resize the canvas to `width` x `height` CSS pixels at the given device pixel ratio.
+///
+/// Reconfigures the surface, recreates the MSAA/depth attachments and updates
+/// the dimensions used for face picking. Called automatically from a
+/// ResizeObserver and when the device pixel ratio changes, but can also be
+/// invoked from JavaScript.
+#[wasm_bindgen]
+pub fn resize(width: u32, height: u32, dpr: f64) {
+    let physical_width = (width as f64 * dpr).round() as u32;
+    let physical_height = (height as f64 * dpr).round() as u32;
+
+    GPU_RESOURCES.with(|gpu| {
+        SURFACE_RESOURCES.with(|surface| {
+            let gpu = gpu.borrow();
+            let surface = surface.borrow();
+
+            if let (Some(gpu), Some(surface)) = (gpu.as_ref(), surface.as_ref()) {
+                let mut surface = surface.borrow_mut();
+                surface.device_pixel_ratio = dpr;
+                renderer::resize_surface(&gpu.device, &mut surface, physical_width, physical_height);
+            }
+        });
+    });
+
+    // Picking works in CSS pixels (mouse offsets), not physical pixels
+    INTERACTION_STATE.with(|state| {
+        if let Some(state) = state.borrow().as_ref() {
+            let mut state = state.borrow_mut();
+            state.canvas_width = width.max(1);
+            state.canvas_height = height.max(1);
+        }
+    });
+}
+
+/// Re-render the current view offscreen at `width` x `height` pixels and
+/// return it as PNG file bytes.
+///
+/// The size is independent of the canvas (up to the device's texture size
+/// limit); the vertical field of view is kept, so a wider image shows more
+/// at the sides. With `transparent_background`, the background is left
+/// transparent instead of the viewer's color. The orientation gizmo, a
+/// control on the canvas rather than part of the picture, is left out.
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub async fn capture_png(width: u32, height: u32, transparent_background: bool) -> Result<Vec<u8>, JsValue> {
+    let scene = SCENE_RENDERER
+        .with(|scene| scene.borrow().clone())
+        .ok_or_else(|| JsValue::from_str("Renderer not initialized"))?;
+    let max_dimension = scene.device.limits().max_texture_dimension_2d;
+    if width == 0 || height == 0 || width > max_dimension || height > max_dimension {
+        return Err(JsValue::from_str(&format!("Capture size must be between 1 and {} pixels", max_dimension)));
+    }
+
+    let texture = renderer::create_offscreen_texture(&scene.device, width, height, scene.format);
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    let mut target = renderer::create_render_target(&scene.device, width, height, scene.format);
+    let background = if transparent_background { wgpu::Color::TRANSPARENT } else { renderer::BACKGROUND_COLOR };
+
+    let mut encoder = scene.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Capture Encoder"),
+    });
+    INTERACTION_STATE.with(|state| {
+        MODEL_RESOURCES.with(|model| {
+            if let (Some(state), Some(model)) = (state.borrow().as_ref(), model.borrow().as_ref()) {
+                scene.draw(&mut encoder, &state.borrow(), &mut model.borrow_mut(), &mut target, &view, background);
+            }
+        });
+    });
+    let readback = renderer::Readback::new(&scene.device, &mut encoder, &texture).map_err(|e| JsValue::from_str(&e))?;
+    scene.queue.submit(std::iter::once(encoder.finish()));
+
+    // Resolves once the GPU has finished the frame and the copy
+    let mapped = js_sys::Promise::new(&mut |resolve, reject| {
+        readback.buffer.slice(..).map_async(wgpu::MapMode::Read, move |result| {
+            let _ = match result {
+                Ok(()) => resolve.call0(&JsValue::NULL),
+                Err(e) => reject.call1(&JsValue::NULL, &JsValue::from_str(&format!("Failed to read back frame: {}", e))),
+            };
+        });
+    });
+    wasm_bindgen_futures::JsFuture::from(mapped).await?;
+
+    Ok(png::encode_rgba(width, height, &readback.rgba(transparent_background)))
 }
 
 // ============================================================================
@@ -189,11 +717,12 @@ pub async fn run() {
     // Get DOM elements
     let (window, canvas, debug_panel, debug_hint) = get_dom_elements();
 
-    // Set canvas size
-    let width = canvas.client_width() as u32;
-    let height = canvas.client_height() as u32;
-    canvas.set_width(width);
-    canvas.set_height(height);
+    // Set canvas size (CSS pixels for picking, physical pixels for the surface)
+    let device_pixel_ratio = window.device_pixel_ratio();
+    let width = (canvas.client_width() as u32).max(1);
+    let height = (canvas.client_height() as u32).max(1);
+    let physical_width = (width as f64 * device_pixel_ratio).round() as u32;
+    let physical_height = (height as f64 * device_pixel_ratio).round() as u32;
 
     // Initialize WebGPU
     let (device, queue, surface, surface_format) = init_webgpu(&canvas).await;
@@ -201,80 +730,78 @@ pub async fn run() {
     let queue = Rc::new(queue);
 
     // Store GPU resources for access from exported functions
+    let uniform_layout = Rc::new(renderer::create_uniform_layout(&device));
+    let shared_mesh_layout = Rc::new(renderer::create_shared_mesh_layout(&device));
     GPU_RESOURCES.with(|gpu| {
         *gpu.borrow_mut() = Some(GpuResources {
             device: device.clone(),
+            uniform_layout: uniform_layout.clone(),
+            shared_mesh_layout: shared_mesh_layout.clone(),
         });
     });
 
-    // Configure surface
+    // Configure surface and create size-dependent attachments
     let config = wgpu::SurfaceConfiguration {
         usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
         format: surface_format,
-        width,
-        height,
+        width: physical_width,
+        height: physical_height,
         present_mode: wgpu::PresentMode::Fifo,
         alpha_mode: wgpu::CompositeAlphaMode::Auto,
         view_formats: vec![],
         desired_maximum_frame_latency: 2,
     };
+    canvas.set_width(physical_width);
+    canvas.set_height(physical_height);
     surface.configure(&device, &config);
+    let target = renderer::create_render_target(&device, physical_width, physical_height, surface_format);
+    let surface_resources = Rc::new(RefCell::new(SurfaceResources {
+        canvas: canvas.clone(),
+        surface,
+        config,
+        target,
+        device_pixel_ratio,
+    }));
+
+    SURFACE_RESOURCES.with(|s| {
+        *s.borrow_mut() = Some(surface_resources.clone());
+    });
 
     // Create rendering resources
-    let (msaa_view, depth_view) = renderer::create_textures(&device, width, height, surface_format);
-    let uniform_buffer = renderer::create_uniform_buffer(&device);
-    let (bind_group_layout, bind_group) = renderer::create_bind_group(&device, &uniform_buffer);
-    let (render_pipeline, transparent_pipeline, wireframe_pipeline) =
-        renderer::create_pipelines(&device, surface_format, &bind_group_layout);
-    let wireframe_buffer = renderer::create_wireframe_buffer(&device);
+    let scene = Rc::new(SceneRenderer::new(device, queue, surface_format, &uniform_layout, &shared_mesh_layout));
+    SCENE_RENDERER.with(|s| {
+        *s.borrow_mut() = Some(scene.clone());
+    });
 
-    // Load default model
-    let (vertices, indices) = parse_model(CUBE_MODEL).expect("Failed to parse default model");
-    let (vertex_buffer, index_buffer) = renderer::create_model_buffers(&device, &vertices, &indices);
-    let model_triangles = extract_triangles(&vertices, &indices);
-
-    let model_resources = Rc::new(RefCell::new(ModelResources {
-        vertex_buffer,
-        index_buffer,
-        num_indices: indices.len() as u32,
-        transparent_mode: false,
-    }));
+    let model_resources = Rc::new(RefCell::new(ModelResources { objects: Vec::new() }));
 
     MODEL_RESOURCES.with(|m| {
         *m.borrow_mut() = Some(model_resources.clone());
     });
 
     // Create interaction state
-    let mut state = InteractionState::new(width, height);
-    state.model_triangles = model_triangles;
-    let state = Rc::new(RefCell::new(state));
+    let state = Rc::new(RefCell::new(InteractionState::new(width, height)));
 
     INTERACTION_STATE.with(|s| {
         *s.borrow_mut() = Some(state.clone());
     });
 
+    // Load default model
+    load_cube_model().expect("Failed to load default model");
+
     // Set up event handlers
     input::setup_mouse_handlers(&canvas, state.clone());
     input::setup_wheel_handler(&canvas, state.clone());
     input::setup_touch_handlers(&canvas, state.clone());
     input::setup_keyboard_handler(&window, &debug_panel, &debug_hint);
+    input::setup_resize_observer(&canvas, &window);
 
     // Create render context and start render loop
     let ctx = RenderContext {
-        device,
-        queue,
-        surface: Rc::new(RefCell::new(surface)),
-        msaa_view: Rc::new(msaa_view),
-        depth_view: Rc::new(depth_view),
-        render_pipeline: Rc::new(render_pipeline),
-        transparent_pipeline: Rc::new(transparent_pipeline),
-        wireframe_pipeline: Rc::new(wireframe_pipeline),
-        wireframe_buffer: Rc::new(wireframe_buffer),
-        bind_group: Rc::new(bind_group),
-        uniform_buffer: Rc::new(uniform_buffer),
+        scene,
+        surface: surface_resources,
         model_resources,
         state,
-        aspect: width as f32 / height as f32,
     };
 
     renderer::start_render_loop(ctx, window, debug_panel);
@@ -325,7 +852,7 @@ async fn init_webgpu(
     });
 
     let surface = instance
-        .create_surface(wgpu::SurfaceTarget::Canvas(canvas.clone()))
+        .create_surface(canvas_surface_target(canvas))
         .expect("Failed to create surface");
 
     let adapter = instance
@@ -352,3 +879,14 @@ async fn init_webgpu(
 
     (device, queue, surface, surface_format)
 }
+
+#[cfg(target_arch = "wasm32")]
+fn canvas_surface_target(canvas: &web_sys::HtmlCanvasElement) -> wgpu::SurfaceTarget<'static> {
+    wgpu::SurfaceTarget::Canvas(canvas.clone())
+}
+
+/// Canvas surfaces only exist on the web; native code renders with `HeadlessRenderer`.
+#[cfg(not(target_arch = "wasm32"))]
+fn canvas_surface_target(_canvas: &web_sys::HtmlCanvasElement) -> wgpu::SurfaceTarget<'static> {
+    panic!("The canvas viewer only runs on wasm32; use HeadlessRenderer for native rendering")
+}
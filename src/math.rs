@@ -11,35 +11,62 @@ pub fn mat4_scale(s: f32) -> Mat4 {
     ]
 }
 
-pub fn mat4_rotate_x(angle: f32) -> Mat4 {
-    let c = angle.cos();
-    let s = angle.sin();
+pub fn mat4_scale_xyz(s: [f32; 3]) -> Mat4 {
     [
-        [1.0, 0.0, 0.0, 0.0],
-        [0.0, c, s, 0.0],
-        [0.0, -s, c, 0.0],
+        [s[0], 0.0, 0.0, 0.0],
+        [0.0, s[1], 0.0, 0.0],
+        [0.0, 0.0, s[2], 0.0],
         [0.0, 0.0, 0.0, 1.0],
     ]
 }
 
-pub fn mat4_rotate_y(angle: f32) -> Mat4 {
-    let c = angle.cos();
-    let s = angle.sin();
+pub fn mat4_translate(t: [f32; 3]) -> Mat4 {
     [
-        [c, 0.0, -s, 0.0],
+        [1.0, 0.0, 0.0, 0.0],
         [0.0, 1.0, 0.0, 0.0],
-        [s, 0.0, c, 0.0],
+        [0.0, 0.0, 1.0, 0.0],
+        [t[0], t[1], t[2], 1.0],
+    ]
+}
+
+/// Rotation matrix from a unit quaternion `[x, y, z, w]`.
+pub fn mat4_from_quat(q: [f32; 4]) -> Mat4 {
+    let [x, y, z, w] = q;
+    [
+        [1.0 - 2.0 * (y * y + z * z), 2.0 * (x * y + z * w), 2.0 * (x * z - y * w), 0.0],
+        [2.0 * (x * y - z * w), 1.0 - 2.0 * (x * x + z * z), 2.0 * (y * z + x * w), 0.0],
+        [2.0 * (x * z + y * w), 2.0 * (y * z - x * w), 1.0 - 2.0 * (x * x + y * y), 0.0],
         [0.0, 0.0, 0.0, 1.0],
     ]
 }
 
+pub const MAT4_IDENTITY: Mat4 = [
+    [1.0, 0.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
+
+/// Perspective projection with a vertical field of view of `fov` radians,
+/// mapping depth onto WebGPU's 0..1 range like `mat4_orthographic`.
 pub fn mat4_perspective(fov: f32, aspect: f32, near: f32, far: f32) -> Mat4 {
     let f = 1.0 / (fov / 2.0).tan();
     [
         [f / aspect, 0.0, 0.0, 0.0],
         [0.0, f, 0.0, 0.0],
-        [0.0, 0.0, (far + near) / (near - far), -1.0],
-        [0.0, 0.0, (2.0 * far * near) / (near - far), 0.0],
+        [0.0, 0.0, far / (near - far), -1.0],
+        [0.0, 0.0, near * far / (near - far), 0.0],
+    ]
+}
+
+/// Orthographic projection of a `2 * half_width` x `2 * half_height` box,
+/// mapping depth onto WebGPU's 0..1 range so the whole near..far span is kept.
+pub fn mat4_orthographic(half_width: f32, half_height: f32, near: f32, far: f32) -> Mat4 {
+    [
+        [1.0 / half_width, 0.0, 0.0, 0.0],
+        [0.0, 1.0 / half_height, 0.0, 0.0],
+        [0.0, 0.0, 1.0 / (near - far), 0.0],
+        [0.0, 0.0, near / (near - far), 1.0],
     ]
 }
 
@@ -114,3 +141,136 @@ pub fn transform_point(p: [f32; 3], m: &Mat4) -> [f32; 3] {
         m[0][2] * p[0] + m[1][2] * p[1] + m[2][2] * p[2] + m[3][2],
     ]
 }
+
+/// Project a point to normalized device coordinates, dividing by w; `None`
+/// for points at or behind the camera plane.
+pub fn project_point(p: [f32; 3], m: &Mat4) -> Option<[f32; 3]> {
+    let w = m[0][3] * p[0] + m[1][3] * p[1] + m[2][3] * p[2] + m[3][3];
+    if w <= 0.0 {
+        return None;
+    }
+    Some(transform_point(p, m).map(|c| c / w))
+}
+
+/// Transform a direction by a 4x4 matrix, ignoring translation.
+pub fn transform_vector(v: [f32; 3], m: &Mat4) -> [f32; 3] {
+    [
+        m[0][0] * v[0] + m[1][0] * v[1] + m[2][0] * v[2],
+        m[0][1] * v[0] + m[1][1] * v[1] + m[2][1] * v[2],
+        m[0][2] * v[0] + m[1][2] * v[1] + m[2][2] * v[2],
+    ]
+}
+
+/// Matrix for transforming normals: the cofactor (inverse transpose times
+/// determinant) of the upper 3x3, sign-corrected so mirrored transforms keep
+/// normals pointing outward. Results must be renormalized.
+pub fn mat4_normal_matrix(m: &Mat4) -> Mat4 {
+    let c0 = cross([m[1][0], m[1][1], m[1][2]], [m[2][0], m[2][1], m[2][2]]);
+    let c1 = cross([m[2][0], m[2][1], m[2][2]], [m[0][0], m[0][1], m[0][2]]);
+    let c2 = cross([m[0][0], m[0][1], m[0][2]], [m[1][0], m[1][1], m[1][2]]);
+    let sign = if mat4_determinant3(m) < 0.0 { -1.0 } else { 1.0 };
+    [
+        [c0[0] * sign, c0[1] * sign, c0[2] * sign, 0.0],
+        [c1[0] * sign, c1[1] * sign, c1[2] * sign, 0.0],
+        [c2[0] * sign, c2[1] * sign, c2[2] * sign, 0.0],
+        [0.0, 0.0, 0.0, 1.0],
+    ]
+}
+
+/// Determinant of the upper-left 3x3 (negative for mirroring transforms).
+pub fn mat4_determinant3(m: &Mat4) -> f32 {
+    dot(
+        [m[0][0], m[0][1], m[0][2]],
+        cross([m[1][0], m[1][1], m[1][2]], [m[2][0], m[2][1], m[2][2]]),
+    )
+}
+
+/// Unit quaternion `[x, y, z, w]` representing a rotation.
+pub type Quat = [f32; 4];
+
+pub const QUAT_IDENTITY: Quat = [0.0, 0.0, 0.0, 1.0];
+
+/// Rotation of `angle` radians about a unit `axis`.
+pub fn quat_from_axis_angle(axis: [f32; 3], angle: f32) -> Quat {
+    let (s, c) = (angle * 0.5).sin_cos();
+    [axis[0] * s, axis[1] * s, axis[2] * s, c]
+}
+
+/// Hamilton product: the rotation `b` followed by `a`.
+pub fn quat_mul(a: Quat, b: Quat) -> Quat {
+    [
+        a[3] * b[0] + a[0] * b[3] + a[1] * b[2] - a[2] * b[1],
+        a[3] * b[1] - a[0] * b[2] + a[1] * b[3] + a[2] * b[0],
+        a[3] * b[2] + a[0] * b[1] - a[1] * b[0] + a[2] * b[3],
+        a[3] * b[3] - a[0] * b[0] - a[1] * b[1] - a[2] * b[2],
+    ]
+}
+
+/// Inverse of a unit quaternion.
+pub fn quat_conjugate(q: Quat) -> Quat {
+    [-q[0], -q[1], -q[2], q[3]]
+}
+
+pub fn quat_normalize(q: Quat) -> Quat {
+    let len = (q[0] * q[0] + q[1] * q[1] + q[2] * q[2] + q[3] * q[3]).sqrt();
+    if len > 0.0 {
+        [q[0] / len, q[1] / len, q[2] / len, q[3] / len]
+    } else {
+        QUAT_IDENTITY
+    }
+}
+
+/// Spherical linear interpolation from `a` (t = 0) to `b` (t = 1) along the
+/// shorter arc.
+pub fn quat_slerp(a: Quat, b: Quat, t: f32) -> Quat {
+    let mut cos_theta = a[0] * b[0] + a[1] * b[1] + a[2] * b[2] + a[3] * b[3];
+    let b = if cos_theta < 0.0 {
+        cos_theta = -cos_theta;
+        [-b[0], -b[1], -b[2], -b[3]]
+    } else {
+        b
+    };
+    // Nearly identical rotations: lerp avoids dividing by sin(theta) ~ 0
+    let (wa, wb) = if cos_theta > 0.9995 {
+        (1.0 - t, t)
+    } else {
+        let theta = cos_theta.acos();
+        let sin_theta = theta.sin();
+        (((1.0 - t) * theta).sin() / sin_theta, (t * theta).sin() / sin_theta)
+    };
+    quat_normalize([0, 1, 2, 3].map(|i| wa * a[i] + wb * b[i]))
+}
+
+/// Shortest-arc rotation taking unit vector `from` onto unit vector `to`.
+pub fn quat_between(from: [f32; 3], to: [f32; 3]) -> Quat {
+    let axis = cross(from, to);
+    quat_normalize([axis[0], axis[1], axis[2], 1.0 + dot(from, to)])
+}
+
+/// Rotation about X by `x` radians, then about Y by `y` radians: the
+/// Euler convention of `get_rotation`/`set_rotation`.
+pub fn quat_from_euler_xy(x: f32, y: f32) -> Quat {
+    quat_mul(quat_from_axis_angle([0.0, 1.0, 0.0], y), quat_from_axis_angle([1.0, 0.0, 0.0], x))
+}
+
+/// Inverse of `quat_from_euler_xy`; any roll in `q` is dropped.
+pub fn quat_to_euler_xy(q: Quat) -> (f32, f32) {
+    let [x, y, z, w] = q;
+    let x_angle = (-2.0 * (y * z - x * w)).clamp(-1.0, 1.0).asin();
+    let y_angle = (2.0 * (x * z + y * w)).atan2(1.0 - 2.0 * (x * x + y * y));
+    (x_angle, y_angle)
+}
+
+/// Project a point in a `width` x `height` viewport onto the arcball sphere,
+/// as a unit vector in view space. Points outside the ball land on its rim.
+pub fn arcball_vector(x: f32, y: f32, width: f32, height: f32) -> [f32; 3] {
+    let radius = width.min(height).max(1.0) * 0.5;
+    let px = (x - width * 0.5) / radius;
+    let py = (height * 0.5 - y) / radius;
+    let d2 = px * px + py * py;
+    if d2 <= 1.0 {
+        [px, py, (1.0 - d2).sqrt()]
+    } else {
+        normalize([px, py, 0.0])
+    }
+}
@@ -1,90 +1,779 @@
 /// Model parsing and geometry utilities.
 
-use crate::vertex::Vertex;
+use std::borrow::Cow;
+use std::collections::HashMap;
 
-/// Parse a text-based model format into vertices and indices.
+use crate::math::{cross, dot, normalize, sub, transform_point, Mat4};
+use crate::vertex::{LineSegment, PointVertex, SharedVertex, Vertex};
+
+/// Color used when a format carries no color information.
+pub const DEFAULT_COLOR: [f32; 4] = [0.8, 0.8, 0.8, 1.0];
+/// Polyline width, in CSS pixels, when none is given.
+pub const DEFAULT_LINE_WIDTH: f32 = 2.0;
+
+/// A vertex before face expansion: position, normal, rgba color.
+pub type RawVertex = ([f32; 3], [f32; 3], [f32; 4]);
+
+/// Index data for a model, stored as u16 when every vertex fits and u32 otherwise.
+#[derive(Clone)]
+pub enum ModelIndices {
+    U16(Vec<u16>),
+    U32(Vec<u32>),
+}
+
+impl ModelIndices {
+    /// Build indices, picking the narrowest format that can address `vertex_count` vertices.
+    pub fn from_u32(indices: Vec<u32>, vertex_count: usize) -> Self {
+        if vertex_count <= u16::MAX as usize + 1 {
+            ModelIndices::U16(indices.into_iter().map(|i| i as u16).collect())
+        } else {
+            ModelIndices::U32(indices)
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            ModelIndices::U16(indices) => indices.len(),
+            ModelIndices::U32(indices) => indices.len(),
+        }
+    }
+
+    pub fn format(&self) -> wgpu::IndexFormat {
+        match self {
+            ModelIndices::U16(_) => wgpu::IndexFormat::Uint16,
+            ModelIndices::U32(_) => wgpu::IndexFormat::Uint32,
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            ModelIndices::U16(indices) => bytemuck::cast_slice(indices),
+            ModelIndices::U32(indices) => bytemuck::cast_slice(indices),
+        }
+    }
+
+    pub fn get(&self, i: usize) -> usize {
+        match self {
+            ModelIndices::U16(indices) => indices[i] as usize,
+            ModelIndices::U32(indices) => indices[i] as usize,
+        }
+    }
+
+    /// Indices with whole triangles rearranged so triangle `order[i]` comes i-th.
+    pub fn reorder_triangles(&self, order: &[u32]) -> ModelIndices {
+        match self {
+            ModelIndices::U16(indices) => ModelIndices::U16(reorder_triangles(indices, 3, order)),
+            ModelIndices::U32(indices) => ModelIndices::U32(reorder_triangles(indices, 3, order)),
+        }
+    }
+}
+
+/// Rearrange items stored `per_triangle` to a triangle so triangle `order[i]` comes i-th.
+pub fn reorder_triangles<T: Copy>(items: &[T], per_triangle: usize, order: &[u32]) -> Vec<T> {
+    order
+        .iter()
+        .flat_map(|&t| &items[t as usize * per_triangle..(t as usize + 1) * per_triangle])
+        .copied()
+        .collect()
+}
+
+/// A triangle mesh whose vertices are shared between faces, and any
+/// polylines drawn with it.
+///
+/// Uses a third of the memory of the expanded form for smooth meshes; the
+/// renderer draws it with `vs_shared`, which looks up each triangle's face id
+/// in `face_ids` rather than storing it per vertex.
+///
+/// The buffers are borrowed when read straight out of a binary model and
+/// owned otherwise.
+pub struct SharedMesh<'a> {
+    pub vertices: Cow<'a, [SharedVertex]>,
+    pub indices: Cow<'a, [u32]>,
+    /// Face id of each triangle (`indices.len() / 3` entries).
+    pub face_ids: Cow<'a, [u32]>,
+    pub lines: Vec<Polyline>,
+}
+
+/// A line through a sequence of points, drawn `width` CSS pixels thick with
+/// joins and caps (see `renderer::create_line_geometry`).
+#[derive(Clone, Debug)]
+pub struct Polyline {
+    /// Face id the line is picked and highlighted by, after the mesh's own faces.
+    pub id: u32,
+    pub positions: Vec<[f32; 3]>,
+    /// Color at each position, blended along the segments between them.
+    pub colors: Vec<[f32; 4]>,
+    pub width: f32,
+}
+
+impl Polyline {
+    /// Segments between consecutive distinct points, each knowing its
+    /// neighbors so joins can be drawn. A polyline that ends where it starts
+    /// is closed, with a join there instead of two caps.
+    pub fn segments(&self) -> Vec<LineSegment> {
+        let mut points: Vec<([f32; 3], [f32; 4])> = Vec::with_capacity(self.positions.len());
+        for (&position, &color) in self.positions.iter().zip(&self.colors) {
+            if points.last().is_none_or(|&(last, _)| last != position) {
+                points.push((position, color));
+            }
+        }
+        let n = points.len();
+        let closed = n > 3 && points[0].0 == points[n - 1].0;
+        let point = |i: usize| points[i].0;
+        // Neighbor as [x, y, z, join flag]; past an open end it is unused
+        let neighbor = |i: Option<usize>| match i {
+            Some(i) => [point(i)[0], point(i)[1], point(i)[2], 1.0],
+            None => [0.0; 4],
+        };
+
+        (0..n.saturating_sub(1))
+            .map(|i| {
+                let prev = match i {
+                    0 if closed => Some(n - 2),
+                    0 => None,
+                    _ => Some(i - 1),
+                };
+                let next = match i + 2 {
+                    j if j < n => Some(j),
+                    _ if closed => Some(1),
+                    _ => None,
+                };
+                LineSegment {
+                    start: point(i),
+                    width: self.width,
+                    end: point(i + 1),
+                    line_id: self.id,
+                    prev: neighbor(prev),
+                    next: neighbor(next),
+                    start_color: points[i].1,
+                    end_color: points[i + 1].1,
+                }
+            })
+            .collect()
+    }
+}
+
+impl SharedMesh<'_> {
+    /// A mesh of polylines alone, with no vertices or triangles.
+    pub fn from_lines(lines: Vec<Polyline>) -> Self {
+        SharedMesh { vertices: Vec::new().into(), indices: Vec::new().into(), face_ids: Vec::new().into(), lines }
+    }
+
+    /// Expand into per-face vertices, for the vertex-buffer render path.
+    pub fn expand(&self) -> (Vec<Vertex>, ModelIndices) {
+        let mut vertices = Vec::with_capacity(self.indices.len());
+        for (tri, &face_id) in self.indices.chunks_exact(3).zip(self.face_ids.iter()) {
+            for &idx in tri {
+                let v = self.vertices[idx as usize];
+                vertices.push(Vertex {
+                    position: v.position,
+                    normal: v.normal,
+                    color: v.color,
+                    face_id,
+                });
+            }
+        }
+        let indices = ModelIndices::from_u32((0..vertices.len() as u32).collect(), vertices.len());
+        (vertices, indices)
+    }
+
+    /// Triangle positions for picking.
+    pub fn triangles(&self) -> Vec<[[f32; 3]; 3]> {
+        self.indices
+            .chunks_exact(3)
+            .map(|tri| [0, 1, 2].map(|i| self.vertices[tri[i] as usize].position))
+            .collect()
+    }
+}
+
+/// Why a text model failed to parse, with the 1-based line and column of the
+/// offending token.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ModelError {
+    /// A token that should be a number or keyword could not be parsed.
+    BadToken { line: usize, column: usize, token: String, expected: &'static str },
+    /// A line has the wrong number of values.
+    MissingFields { line: usize, column: usize, expected: &'static str, found: usize },
+    /// A face or line refers to a vertex that was never defined.
+    IndexOutOfRange { line: usize, column: usize, index: u32, vertex_count: usize },
+    /// A face uses the same vertex more than once.
+    DegenerateFace { line: usize, column: usize },
+    /// A vertex has a NaN or infinite position, normal or color value.
+    NanCoordinate { line: usize, column: usize },
+    /// The model defines no vertices, or no faces or lines (reported at the end of the text).
+    Empty { line: usize, column: usize, missing: &'static str },
+}
+
+impl ModelError {
+    /// Stable snake_case name of the variant, for callers outside Rust.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ModelError::BadToken { .. } => "bad_token",
+            ModelError::MissingFields { .. } => "missing_fields",
+            ModelError::IndexOutOfRange { .. } => "index_out_of_range",
+            ModelError::DegenerateFace { .. } => "degenerate_face",
+            ModelError::NanCoordinate { .. } => "nan_coordinate",
+            ModelError::Empty { .. } => "empty",
+        }
+    }
+
+    pub fn line(&self) -> usize {
+        match *self {
+            ModelError::BadToken { line, .. }
+            | ModelError::MissingFields { line, .. }
+            | ModelError::IndexOutOfRange { line, .. }
+            | ModelError::DegenerateFace { line, .. }
+            | ModelError::NanCoordinate { line, .. }
+            | ModelError::Empty { line, .. } => line,
+        }
+    }
+
+    pub fn column(&self) -> usize {
+        match *self {
+            ModelError::BadToken { column, .. }
+            | ModelError::MissingFields { column, .. }
+            | ModelError::IndexOutOfRange { column, .. }
+            | ModelError::DegenerateFace { column, .. }
+            | ModelError::NanCoordinate { column, .. }
+            | ModelError::Empty { column, .. } => column,
+        }
+    }
+}
+
+impl std::fmt::Display for ModelError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}: ", self.line(), self.column())?;
+        match self {
+            ModelError::BadToken { token, expected, .. } => write!(f, "expected {}, found '{}'", expected, token),
+            ModelError::MissingFields { expected, found, .. } => {
+                write!(f, "expected {} values, found {}", expected, found)
+            }
+            ModelError::IndexOutOfRange { index, vertex_count, .. } => {
+                write!(f, "vertex index {} out of range ({} vertices defined)", index, vertex_count)
+            }
+            ModelError::DegenerateFace { .. } => write!(f, "face uses the same vertex more than once"),
+            ModelError::NanCoordinate { .. } => write!(f, "vertex value is NaN or infinite"),
+            ModelError::Empty { missing, .. } => write!(f, "no {} found in model", missing),
+        }
+    }
+}
+
+impl std::error::Error for ModelError {}
+
+/// Whitespace-separated tokens of a line with their 1-based columns.
+fn tokens_with_columns(line: &str) -> Vec<(usize, &str)> {
+    line.split_whitespace()
+        .map(|token| {
+            let offset = token.as_ptr() as usize - line.as_ptr() as usize;
+            (line[..offset].chars().count() + 1, token)
+        })
+        .collect()
+}
+
+/// Parse a text-based model format into a shared-vertex mesh.
 ///
 /// Format:
-/// - `v x y z nx ny nz r g b [a]` or `vertex ...` - position, normal, rgb, optional alpha (default 1.0)
-/// - `f i0 i1 i2` or `face/tri/triangle ...` - Define a triangle face with vertex indices
+/// - `v x y z [nx ny nz] [r g b [a]]` or `vertex ...` - position, optional normal,
+///   optional rgb (default gray) with optional alpha (default 1.0)
+/// - `f i0 i1 i2 [i3 ...]` or `face/tri/triangle ...` - Define a face with vertex
+///   indices; polygons are triangulated and pick as one face
+/// - `l i0 i1 [i2 ...]` or `line ...` - A polyline through the vertices, in
+///   their colors; it picks as one face, numbered after all the faces
+/// - `line_width w` - Width in CSS pixels of the lines that follow
+///   (default `DEFAULT_LINE_WIDTH`)
+/// - `normals flat` or `normals smooth [crease_deg]` - Generate normals for every
+///   vertex instead of using the given ones
 /// - Lines starting with `#` are comments
-pub fn parse_model(text: &str) -> Result<(Vec<Vertex>, Vec<u16>), String> {
-    let mut raw_vertices: Vec<([f32; 3], [f32; 3], [f32; 4])> = Vec::new();
-    let mut raw_faces: Vec<[u16; 3]> = Vec::new();
+///
+/// Vertices without normals get smooth normals with a
+/// `DEFAULT_CREASE_ANGLE_DEG` crease angle unless a `normals` line says otherwise.
+pub fn parse_model(text: &str) -> Result<SharedMesh<'static>, ModelError> {
+    let mut raw_vertices: Vec<RawVertex> = Vec::new();
+    let mut has_normal: Vec<bool> = Vec::new();
+    let mut raw_faces: Vec<Vec<u32>> = Vec::new();
+    // Line and per-index columns of each face, for range errors reported after parsing
+    let mut face_locations: Vec<(usize, Vec<usize>)> = Vec::new();
+    // Polylines as vertex indices with their widths, and their locations likewise
+    let mut raw_lines: Vec<(Vec<u32>, f32)> = Vec::new();
+    let mut line_locations: Vec<(usize, Vec<usize>)> = Vec::new();
+    let mut line_width = DEFAULT_LINE_WIDTH;
+    let mut normal_mode: Option<NormalMode> = None;
+    let mut line_count = 0;
 
-    for line in text.lines() {
-        let line = line.trim();
-        if line.is_empty() || line.starts_with('#') {
+    for (line_idx, line) in text.lines().enumerate() {
+        let line_no = line_idx + 1;
+        line_count = line_no;
+        let parts = tokens_with_columns(line);
+        let Some(&(keyword_column, keyword)) = parts.first() else {
             continue;
-        }
-
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.is_empty() {
+        };
+        if keyword.starts_with('#') {
             continue;
         }
 
-        match parts[0] {
+        let bad_token = |(column, token): (usize, &str), expected| ModelError::BadToken {
+            line: line_no,
+            column,
+            token: token.to_string(),
+            expected,
+        };
+
+        match keyword {
             "v" | "vertex" => {
-                if parts.len() < 10 {
-                    return Err(format!("Invalid vertex line: {}", line));
-                }
-                let position = [
-                    parts[1].parse::<f32>().map_err(|e| e.to_string())?,
-                    parts[2].parse::<f32>().map_err(|e| e.to_string())?,
-                    parts[3].parse::<f32>().map_err(|e| e.to_string())?,
-                ];
-                let normal = [
-                    parts[4].parse::<f32>().map_err(|e| e.to_string())?,
-                    parts[5].parse::<f32>().map_err(|e| e.to_string())?,
-                    parts[6].parse::<f32>().map_err(|e| e.to_string())?,
-                ];
-                let alpha = if parts.len() >= 11 {
-                    parts[10].parse::<f32>().map_err(|e| e.to_string())?
-                } else {
-                    1.0
+                let values = parts[1..]
+                    .iter()
+                    .map(|&(column, token)| {
+                        let value = token.parse::<f32>().map_err(|_| bad_token((column, token), "a number"))?;
+                        if !value.is_finite() {
+                            return Err(ModelError::NanCoordinate { line: line_no, column });
+                        }
+                        Ok(value)
+                    })
+                    .collect::<Result<Vec<f32>, ModelError>>()?;
+                let v = values.as_slice();
+                let (normal, color) = match v.len() {
+                    3 => (None, DEFAULT_COLOR),
+                    6 => (None, [v[3], v[4], v[5], 1.0]),
+                    7 => (None, [v[3], v[4], v[5], v[6]]),
+                    9 => (Some([v[3], v[4], v[5]]), [v[6], v[7], v[8], 1.0]),
+                    10 => (Some([v[3], v[4], v[5]]), [v[6], v[7], v[8], v[9]]),
+                    found => {
+                        return Err(ModelError::MissingFields {
+                            line: line_no,
+                            column: keyword_column,
+                            expected: "3, 6, 7, 9 or 10",
+                            found,
+                        })
+                    }
                 };
-                let color = [
-                    parts[7].parse::<f32>().map_err(|e| e.to_string())?,
-                    parts[8].parse::<f32>().map_err(|e| e.to_string())?,
-                    parts[9].parse::<f32>().map_err(|e| e.to_string())?,
-                    alpha,
-                ];
-                raw_vertices.push((position, normal, color));
+                raw_vertices.push(([v[0], v[1], v[2]], normal.unwrap_or([0.0; 3]), color));
+                has_normal.push(normal.is_some());
             }
             "f" | "face" | "tri" | "triangle" => {
                 if parts.len() < 4 {
-                    return Err(format!("Invalid face line: {}", line));
+                    return Err(ModelError::MissingFields {
+                        line: line_no,
+                        column: keyword_column,
+                        expected: "at least 3",
+                        found: parts.len() - 1,
+                    });
                 }
-                raw_faces.push([
-                    parts[1].parse::<u16>().map_err(|e| e.to_string())?,
-                    parts[2].parse::<u16>().map_err(|e| e.to_string())?,
-                    parts[3].parse::<u16>().map_err(|e| e.to_string())?,
-                ]);
+                let mut face = Vec::with_capacity(parts.len() - 1);
+                let mut columns = Vec::with_capacity(parts.len() - 1);
+                for &(column, token) in &parts[1..] {
+                    let index = token.parse::<u32>().map_err(|_| bad_token((column, token), "a vertex index"))?;
+                    if face.contains(&index) {
+                        return Err(ModelError::DegenerateFace { line: line_no, column });
+                    }
+                    face.push(index);
+                    columns.push(column);
+                }
+                raw_faces.push(face);
+                face_locations.push((line_no, columns));
+            }
+            "l" | "line" => {
+                if parts.len() < 3 {
+                    return Err(ModelError::MissingFields {
+                        line: line_no,
+                        column: keyword_column,
+                        expected: "at least 2",
+                        found: parts.len() - 1,
+                    });
+                }
+                let mut indices = Vec::with_capacity(parts.len() - 1);
+                let mut columns = Vec::with_capacity(parts.len() - 1);
+                for &(column, token) in &parts[1..] {
+                    indices.push(token.parse::<u32>().map_err(|_| bad_token((column, token), "a vertex index"))?);
+                    columns.push(column);
+                }
+                raw_lines.push((indices, line_width));
+                line_locations.push((line_no, columns));
+            }
+            "line_width" => {
+                let Some(&width) = parts.get(1) else {
+                    return Err(ModelError::MissingFields {
+                        line: line_no,
+                        column: keyword_column,
+                        expected: "1",
+                        found: 0,
+                    });
+                };
+                line_width = width
+                    .1
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|w| w.is_finite() && *w > 0.0)
+                    .ok_or_else(|| bad_token(width, "a positive line width"))?;
+            }
+            "normals" => {
+                normal_mode = Some(match parts.get(1).copied() {
+                    Some((_, "flat")) => NormalMode::Flat,
+                    Some((_, "smooth")) => NormalMode::Smooth {
+                        crease_angle_deg: match parts.get(2).copied() {
+                            Some(angle) => angle.1.parse::<f32>().map_err(|_| bad_token(angle, "a crease angle"))?,
+                            None => DEFAULT_CREASE_ANGLE_DEG,
+                        },
+                    },
+                    Some(other) => return Err(bad_token(other, "'flat' or 'smooth'")),
+                    None => {
+                        return Err(ModelError::MissingFields {
+                            line: line_no,
+                            column: keyword_column,
+                            expected: "1 or 2",
+                            found: 0,
+                        })
+                    }
+                });
             }
             _ => {}
         }
     }
 
     if raw_vertices.is_empty() {
+        return Err(ModelError::Empty { line: line_count, column: 1, missing: "vertices" });
+    }
+    if raw_faces.is_empty() && raw_lines.is_empty() {
+        return Err(ModelError::Empty { line: line_count, column: 1, missing: "faces or lines" });
+    }
+
+    let faces_and_lines = raw_faces.iter().chain(raw_lines.iter().map(|(indices, _)| indices));
+    for (face, (line, columns)) in faces_and_lines.zip(face_locations.iter().chain(&line_locations)) {
+        for (&index, &column) in face.iter().zip(columns) {
+            if index as usize >= raw_vertices.len() {
+                return Err(ModelError::IndexOutOfRange {
+                    line: *line,
+                    column,
+                    index,
+                    vertex_count: raw_vertices.len(),
+                });
+            }
+        }
+    }
+
+    // Split polygons into triangles that all keep the polygon's face id
+    let mut indices = Vec::with_capacity(raw_faces.len() * 3);
+    let mut face_ids = Vec::with_capacity(raw_faces.len());
+    for (face_id, face) in raw_faces.iter().enumerate() {
+        let positions: Vec<[f32; 3]> = face.iter().map(|&idx| raw_vertices[idx as usize].0).collect();
+        for tri in triangulate_polygon(&positions) {
+            indices.extend(tri.map(|corner| face[corner]));
+            face_ids.push(face_id as u32);
+        }
+    }
+
+    let lines = raw_lines
+        .iter()
+        .enumerate()
+        .map(|(i, (line, width))| Polyline {
+            id: (raw_faces.len() + i) as u32,
+            positions: line.iter().map(|&idx| raw_vertices[idx as usize].0).collect(),
+            colors: line.iter().map(|&idx| raw_vertices[idx as usize].2).collect(),
+            width: *width,
+        })
+        .collect();
+
+    let mesh = SharedMesh {
+        vertices: raw_vertices
+            .into_iter()
+            .map(|(position, normal, color)| SharedVertex { position, normal, color })
+            .collect(),
+        indices: indices.into(),
+        face_ids: face_ids.into(),
+        lines,
+    };
+
+    Ok(match normal_mode {
+        Some(mode) => generate_normals(&mesh, mode, |_| false),
+        None if has_normal.contains(&false) => generate_normals(
+            &mesh,
+            NormalMode::Smooth { crease_angle_deg: DEFAULT_CREASE_ANGLE_DEG },
+            |idx| has_normal[idx as usize],
+        ),
+        None => mesh,
+    })
+}
+
+/// Split a planar polygon into triangles, as corner indices into `positions`.
+///
+/// Convex polygons are fanned from the first corner; concave ones are ear
+/// clipped in the polygon's best-fit plane. Winding follows the corner order.
+/// Self-intersecting polygons, which have no ears to clip, fall back to a fan.
+pub fn triangulate_polygon(positions: &[[f32; 3]]) -> Vec<[usize; 3]> {
+    let n = positions.len();
+    let fan = || (1..n - 1).map(|i| [0, i, i + 1]).collect();
+    if n <= 3 {
+        return fan();
+    }
+
+    // Newell's method gives the polygon normal; drop its largest axis to get
+    // 2D coordinates with the polygon counter-clockwise
+    let mut normal = [0.0f32; 3];
+    for i in 0..n {
+        let (a, b) = (positions[i], positions[(i + 1) % n]);
+        normal[0] += (a[1] - b[1]) * (a[2] + b[2]);
+        normal[1] += (a[2] - b[2]) * (a[0] + b[0]);
+        normal[2] += (a[0] - b[0]) * (a[1] + b[1]);
+    }
+    let axis = (0..3)
+        .max_by(|&i, &j| normal[i].abs().total_cmp(&normal[j].abs()))
+        .unwrap_or(2);
+    let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+    let flip = if normal[axis] < 0.0 { -1.0 } else { 1.0 };
+    let points: Vec<[f32; 2]> = positions.iter().map(|p| [p[u], p[v] * flip]).collect();
+
+    let turn = |a: [f32; 2], b: [f32; 2], c: [f32; 2]| (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0]);
+    if (0..n).all(|i| turn(points[i], points[(i + 1) % n], points[(i + 2) % n]) >= 0.0) {
+        return fan();
+    }
+
+    let inside = |p: [f32; 2], a: [f32; 2], b: [f32; 2], c: [f32; 2]| {
+        turn(a, b, p) >= 0.0 && turn(b, c, p) >= 0.0 && turn(c, a, p) >= 0.0
+    };
+    let mut remaining: Vec<usize> = (0..n).collect();
+    let mut triangles = Vec::with_capacity(n - 2);
+    while remaining.len() > 3 {
+        let m = remaining.len();
+        let ear = (0..m).find(|&i| {
+            let (a, b, c) = (remaining[(i + m - 1) % m], remaining[i], remaining[(i + 1) % m]);
+            turn(points[a], points[b], points[c]) > 0.0
+                && remaining
+                    .iter()
+                    .filter(|&&p| p != a && p != b && p != c)
+                    .all(|&p| !inside(points[p], points[a], points[b], points[c]))
+        });
+        let Some(i) = ear else {
+            return fan();
+        };
+        triangles.push([remaining[(i + m - 1) % m], remaining[i], remaining[(i + 1) % m]]);
+        remaining.remove(i);
+    }
+    triangles.push([remaining[0], remaining[1], remaining[2]]);
+    triangles
+}
+
+/// Crease angle used when the text format leaves normals out.
+pub const DEFAULT_CREASE_ANGLE_DEG: f32 = 30.0;
+
+/// How `generate_normals` derives vertex normals.
+#[derive(Clone, Copy)]
+pub enum NormalMode {
+    /// One normal per triangle, so every face shades flat.
+    Flat,
+    /// Angle-weighted average of the normals of all faces meeting at a vertex
+    /// position, skipping faces more than `crease_angle_deg` away from the
+    /// face being shaded so hard edges stay sharp.
+    Smooth { crease_angle_deg: f32 },
+}
+
+/// Compute vertex normals for a mesh.
+///
+/// Faces meet at a vertex when they share its position, not just its index,
+/// so seams with duplicated vertices are smoothed too. Vertices for which
+/// `keep` returns true retain their existing normal. Vertices are split
+/// wherever one needs different normals in different faces, so the result
+/// may have more vertices than the input; face ids are unchanged.
+pub fn generate_normals(mesh: &SharedMesh, mode: NormalMode, keep: impl Fn(u32) -> bool) -> SharedMesh<'static> {
+    let position = |idx: u32| mesh.vertices[idx as usize].position;
+    let triangles: Vec<[u32; 3]> = mesh.indices.chunks_exact(3).map(|t| [t[0], t[1], t[2]]).collect();
+
+    let face_normals: Vec<[f32; 3]> = triangles
+        .iter()
+        .map(|t| triangle_normal(position(t[0]), position(t[1]), position(t[2])))
+        .collect();
+
+    // Interior angle of each triangle corner, for angle weighting
+    let corner_angles: Vec<[f32; 3]> = triangles
+        .iter()
+        .map(|t| {
+            [0, 1, 2].map(|k| {
+                let p = position(t[k]);
+                let a = normalize(sub(position(t[(k + 1) % 3]), p));
+                let b = normalize(sub(position(t[(k + 2) % 3]), p));
+                dot(a, b).clamp(-1.0, 1.0).acos()
+            })
+        })
+        .collect();
+
+    // Corners (triangle, corner) grouped by exact vertex position
+    let mut corners_at: HashMap<[u32; 3], Vec<(usize, usize)>> = HashMap::new();
+    if let NormalMode::Smooth { .. } = mode {
+        for (t, tri) in triangles.iter().enumerate() {
+            for (k, &idx) in tri.iter().enumerate() {
+                corners_at.entry(position(idx).map(f32::to_bits)).or_default().push((t, k));
+            }
+        }
+    }
+
+    let mut vertices: Vec<SharedVertex> = Vec::with_capacity(mesh.vertices.len());
+    let mut indices: Vec<u32> = Vec::with_capacity(mesh.indices.len());
+    let mut split: HashMap<(u32, [u32; 3]), u32> = HashMap::new();
+
+    for (t, tri) in triangles.iter().enumerate() {
+        for &idx in tri {
+            let original = mesh.vertices[idx as usize];
+            let normal = if keep(idx) {
+                original.normal
+            } else {
+                match mode {
+                    NormalMode::Flat => face_normals[t],
+                    NormalMode::Smooth { crease_angle_deg } => {
+                        let min_cos = crease_angle_deg.to_radians().cos();
+                        let mut sum = [0.0; 3];
+                        for &(other, k) in &corners_at[&original.position.map(f32::to_bits)] {
+                            if dot(face_normals[t], face_normals[other]) >= min_cos {
+                                let weight = corner_angles[other][k];
+                                for (s, n) in sum.iter_mut().zip(face_normals[other]) {
+                                    *s += n * weight;
+                                }
+                            }
+                        }
+                        if sum == [0.0; 3] {
+                            face_normals[t]
+                        } else {
+                            normalize(sum)
+                        }
+                    }
+                }
+            };
+
+            let new_idx = *split.entry((idx, normal.map(f32::to_bits))).or_insert_with(|| {
+                vertices.push(SharedVertex { normal, ..original });
+                vertices.len() as u32 - 1
+            });
+            indices.push(new_idx);
+        }
+    }
+
+    SharedMesh {
+        vertices: vertices.into(),
+        indices: indices.into(),
+        face_ids: mesh.face_ids.to_vec().into(),
+        lines: mesh.lines.clone(),
+    }
+}
+
+/// Magic bytes at the start of a binary model.
+pub const BINARY_MODEL_MAGIC: &[u8; 4] = b"VPLB";
+/// Binary model format version understood by `parse_model_binary`.
+pub const BINARY_MODEL_VERSION: u16 = 1;
+/// Flag: ignore the stored normals and generate smooth ones on load.
+pub const BINARY_FLAG_GENERATE_NORMALS: u16 = 1 << 0;
+/// Flag: a face id per triangle follows the indices, so several triangles can
+/// pick and highlight as one face.
+pub const BINARY_FLAG_FACE_IDS: u16 = 1 << 1;
+
+const BINARY_HEADER_SIZE: usize = 16;
+
+/// Parse a binary model, borrowing its vertex and index data in place.
+///
+/// Layout (all little-endian, no padding):
+/// - 16-byte header: magic `VPLB`, version `u16`, flags `u16`,
+///   vertex count `u32`, triangle count `u32`
+/// - vertices: position `3 x f32`, normal `3 x f32`, color `4 x f32` (40 bytes each,
+///   identical to `SharedVertex`)
+/// - indices: `3 x u32` per triangle
+/// - with `BINARY_FLAG_FACE_IDS`: one `u32` face id per triangle
+///
+/// `bytes` must be 4-byte aligned; every section is a multiple of 4 bytes so
+/// the vertex and index sections are cast without copying.
+pub fn parse_model_binary(bytes: &[u8]) -> Result<SharedMesh<'_>, String> {
+    if bytes.len() < BINARY_HEADER_SIZE || &bytes[0..4] != BINARY_MODEL_MAGIC {
+        return Err("Not a binary model (missing VPLB header)".to_string());
+    }
+    let read_u16 = |at: usize| u16::from_le_bytes([bytes[at], bytes[at + 1]]);
+    let read_u32 = |at: usize| u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]]);
+
+    let version = read_u16(4);
+    if version != BINARY_MODEL_VERSION {
+        return Err(format!("Unsupported binary model version {}", version));
+    }
+    let flags = read_u16(6);
+    let vertex_count = read_u32(8) as usize;
+    let triangle_count = read_u32(12) as usize;
+    if vertex_count == 0 {
         return Err("No vertices found in model".to_string());
     }
-    if raw_faces.is_empty() {
+    if triangle_count == 0 {
         return Err("No faces found in model".to_string());
     }
 
-    // Expand vertices so each face has unique vertices with face_id
-    let mut vertices = Vec::new();
-    let mut indices = Vec::new();
+    // Counts come from the file; on 32-bit targets their byte sizes can overflow
+    let too_large =
+        || format!("Binary model of {} vertices and {} triangles is too large", vertex_count, triangle_count);
+    let vertex_bytes = vertex_count.checked_mul(std::mem::size_of::<SharedVertex>()).ok_or_else(too_large)?;
+    let index_bytes = triangle_count.checked_mul(3 * 4).ok_or_else(too_large)?;
+    let face_id_bytes = if flags & BINARY_FLAG_FACE_IDS != 0 { triangle_count.checked_mul(4) } else { Some(0) };
+    let expected = face_id_bytes
+        .and_then(|b| b.checked_add(index_bytes))
+        .and_then(|b| b.checked_add(vertex_bytes))
+        .and_then(|b| b.checked_add(BINARY_HEADER_SIZE))
+        .ok_or_else(too_large)?;
+    if bytes.len() != expected {
+        return Err(format!(
+            "Binary model is {} bytes, expected {} for {} vertices and {} triangles",
+            bytes.len(),
+            expected,
+            vertex_count,
+            triangle_count
+        ));
+    }
 
-    for (face_id, face) in raw_faces.iter().enumerate() {
-        let base_idx = vertices.len() as u16;
-        for &idx in face.iter() {
-            let (position, normal, color) = raw_vertices[idx as usize];
+    let cast_error = |e: bytemuck::PodCastError| format!("Binary model data is not 4-byte aligned: {:?}", e);
+    let (vertex_section, rest) = bytes[BINARY_HEADER_SIZE..].split_at(vertex_bytes);
+    let (index_section, face_id_section) = rest.split_at(index_bytes);
+    let vertices: &[SharedVertex] = bytemuck::try_cast_slice(vertex_section).map_err(cast_error)?;
+    let indices: &[u32] = bytemuck::try_cast_slice(index_section).map_err(cast_error)?;
+
+    if let Some(pos) = indices.iter().position(|&idx| idx as usize >= vertex_count) {
+        return Err(format!(
+            "Triangle {} uses vertex index {} but only {} vertices are defined",
+            pos / 3,
+            indices[pos],
+            vertex_count
+        ));
+    }
+
+    let face_ids = if flags & BINARY_FLAG_FACE_IDS != 0 {
+        Cow::Borrowed(bytemuck::try_cast_slice(face_id_section).map_err(cast_error)?)
+    } else {
+        Cow::Owned((0..triangle_count as u32).collect())
+    };
+    let mesh = SharedMesh {
+        vertices: Cow::Borrowed(vertices),
+        indices: Cow::Borrowed(indices),
+        face_ids,
+        lines: Vec::new(),
+    };
+
+    if flags & BINARY_FLAG_GENERATE_NORMALS != 0 {
+        let mode = NormalMode::Smooth { crease_angle_deg: DEFAULT_CREASE_ANGLE_DEG };
+        return Ok(generate_normals(&mesh, mode, |_| false));
+    }
+    Ok(mesh)
+}
+
+/// Expand triangles so each face has unique vertices with face_id.
+pub fn expand_triangles(triangles: &[[RawVertex; 3]]) -> (Vec<Vertex>, ModelIndices) {
+    expand_faces(triangles, 0..triangles.len() as u32)
+}
+
+/// Expand triangles so each has unique vertices, tagged with the face id
+/// `face_ids` gives it, so triangles of one polygon can share an id.
+pub fn expand_faces(
+    triangles: &[[RawVertex; 3]],
+    face_ids: impl IntoIterator<Item = u32>,
+) -> (Vec<Vertex>, ModelIndices) {
+    let mut vertices = Vec::with_capacity(triangles.len() * 3);
+    let mut indices = Vec::with_capacity(triangles.len() * 3);
+
+    for (triangle, face_id) in triangles.iter().zip(face_ids) {
+        let base_idx = vertices.len() as u32;
+        for &(position, normal, color) in triangle.iter() {
             vertices.push(Vertex {
                 position,
                 normal,
                 color,
-                face_id: face_id as u32,
+                face_id,
             });
         }
         indices.push(base_idx);
@@ -92,20 +781,903 @@ pub fn parse_model(text: &str) -> Result<(Vec<Vertex>, Vec<u16>), String> {
         indices.push(base_idx + 2);
     }
 
-    Ok((vertices, indices))
+    let indices = ModelIndices::from_u32(indices, vertices.len());
+    (vertices, indices)
+}
+
+/// Parse a Wavefront OBJ file into vertices and indices.
+///
+/// Supports:
+/// - `v x y z`, `vn x y z`, `vt u v` (texture coordinates are validated but unused)
+/// - `f` with `v`, `v/vt`, `v//vn` or `v/vt/vn` corners, 1-based or negative (relative) indices
+/// - Polygons with more than three corners (ear-clipped if concave), picked as one face
+/// - `o`/`g` groups (accepted; geometry from all groups is merged)
+/// - `usemtl name`, colored with the `Kd`/`d` of `name` from `mtl_text` if given
+///
+/// Faces without `vn` indices get a flat normal computed from their winding.
+pub fn parse_obj(text: &str, mtl_text: Option<&str>) -> Result<(Vec<Vertex>, ModelIndices), String> {
+    let materials = mtl_text.map(parse_mtl).transpose()?.unwrap_or_default();
+
+    let mut positions: Vec<[f32; 3]> = Vec::new();
+    let mut normals: Vec<[f32; 3]> = Vec::new();
+    let mut tex_coord_count = 0usize;
+    let mut color = DEFAULT_COLOR;
+    let mut triangles: Vec<[RawVertex; 3]> = Vec::new();
+    // Index of the `f` record each triangle came from
+    let mut face_ids: Vec<u32> = Vec::new();
+    let mut face_count = 0u32;
+
+    for (line_no, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let line_err = |msg: &str| format!("OBJ line {}: {}", line_no + 1, msg);
+
+        match parts[0] {
+            "v" => positions.push(parse_vec3(&parts).map_err(|e| line_err(&e))?),
+            "vn" => normals.push(parse_vec3(&parts).map_err(|e| line_err(&e))?),
+            "vt" => tex_coord_count += 1,
+            "f" => {
+                if parts.len() < 4 {
+                    return Err(line_err("face needs at least 3 vertices"));
+                }
+                let mut corners = Vec::with_capacity(parts.len() - 1);
+                for corner in &parts[1..] {
+                    let mut fields = corner.split('/');
+                    let v = fields.next().unwrap_or("");
+                    let vt = fields.next().unwrap_or("");
+                    let vn = fields.next().unwrap_or("");
+
+                    let position = positions[resolve_obj_index(v, positions.len()).map_err(|e| line_err(&e))?];
+                    if !vt.is_empty() {
+                        resolve_obj_index(vt, tex_coord_count).map_err(|e| line_err(&e))?;
+                    }
+                    let normal = if vn.is_empty() {
+                        None
+                    } else {
+                        Some(normals[resolve_obj_index(vn, normals.len()).map_err(|e| line_err(&e))?])
+                    };
+                    corners.push((position, normal));
+                }
+
+                let corner_positions: Vec<[f32; 3]> = corners.iter().map(|&(position, _)| position).collect();
+                for tri in triangulate_polygon(&corner_positions) {
+                    let tri = tri.map(|corner| corners[corner]);
+                    let face_normal = triangle_normal(tri[0].0, tri[1].0, tri[2].0);
+                    triangles.push(tri.map(|(position, normal)| {
+                        (position, normal.unwrap_or(face_normal), color)
+                    }));
+                    face_ids.push(face_count);
+                }
+                face_count += 1;
+            }
+            "usemtl" => {
+                let name = parts.get(1).copied().unwrap_or("");
+                color = materials.get(name).copied().unwrap_or(DEFAULT_COLOR);
+            }
+            // Everything else, including groups and objects, which are merged into a single mesh
+            _ => {}
+        }
+    }
+
+    if triangles.is_empty() {
+        return Err("No faces found in OBJ".to_string());
+    }
+
+    Ok(expand_faces(&triangles, face_ids))
+}
+
+/// Parse the diffuse colors from a Wavefront MTL file, keyed by material name.
+///
+/// Only `newmtl`, `Kd` and the opacity statements `d` / `Tr` are read.
+fn parse_mtl(text: &str) -> Result<HashMap<String, [f32; 4]>, String> {
+    let mut materials = HashMap::new();
+    let mut current: Option<String> = None;
+
+    for (line_no, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let line_err = |msg: &str| format!("MTL line {}: {}", line_no + 1, msg);
+
+        match parts[0] {
+            "newmtl" => {
+                let name = parts.get(1).copied().unwrap_or("").to_string();
+                materials.insert(name.clone(), DEFAULT_COLOR);
+                current = Some(name);
+            }
+            "Kd" | "d" | "Tr" => {
+                let Some(color) = current.as_ref().and_then(|name| materials.get_mut(name)) else {
+                    return Err(line_err("material property before newmtl"));
+                };
+                match parts[0] {
+                    "Kd" => {
+                        let [r, g, b] = parse_vec3(&parts).map_err(|e| line_err(&e))?;
+                        color[0] = r;
+                        color[1] = g;
+                        color[2] = b;
+                    }
+                    "d" => color[3] = parse_f32(parts.get(1)).map_err(|e| line_err(&e))?,
+                    _ => color[3] = 1.0 - parse_f32(parts.get(1)).map_err(|e| line_err(&e))?,
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(materials)
+}
+
+/// Parse a binary or ASCII STL file into vertices and indices.
+///
+/// STL carries no color, so every triangle uses `color`. Facet normals are
+/// taken from the file unless they are zero, in which case they are computed
+/// from the triangle's winding.
+pub fn parse_stl(bytes: &[u8], color: [f32; 4]) -> Result<(Vec<Vertex>, ModelIndices), String> {
+    let facets = if is_binary_stl(bytes) {
+        parse_binary_stl(bytes)?
+    } else {
+        let text = std::str::from_utf8(bytes).map_err(|_| "STL is neither valid binary nor ASCII".to_string())?;
+        parse_ascii_stl(text)?
+    };
+
+    if facets.is_empty() {
+        return Err("No facets found in STL".to_string());
+    }
+
+    let triangles: Vec<[RawVertex; 3]> = facets
+        .iter()
+        .map(|(normal, corners)| {
+            let normal = if *normal == [0.0; 3] {
+                triangle_normal(corners[0], corners[1], corners[2])
+            } else {
+                normalize(*normal)
+            };
+            corners.map(|position| (position, normal, color))
+        })
+        .collect();
+    Ok(expand_triangles(&triangles))
+}
+
+/// A binary STL is an 80-byte header, a u32 facet count and 50 bytes per facet.
+///
+/// Binary files may also start with `solid`, so the size check takes priority.
+fn is_binary_stl(bytes: &[u8]) -> bool {
+    if bytes.len() < 84 {
+        return false;
+    }
+    bytes.len() as u64 == binary_stl_size(bytes).1 || !bytes.starts_with(b"solid")
+}
+
+/// Facet count from a binary STL header, and the file size it implies. The
+/// size is in u64 so a corrupt count can't wrap it on 32-bit targets.
+fn binary_stl_size(bytes: &[u8]) -> (u32, u64) {
+    let count = u32::from_le_bytes([bytes[80], bytes[81], bytes[82], bytes[83]]);
+    (count, 84 + count as u64 * 50)
+}
+
+type StlFacet = ([f32; 3], [[f32; 3]; 3]);
+
+fn parse_binary_stl(bytes: &[u8]) -> Result<Vec<StlFacet>, String> {
+    let (count, size) = binary_stl_size(bytes);
+    if (bytes.len() as u64) < size {
+        return Err(format!("Binary STL truncated: {} facets need {} bytes, got {}", count, size, bytes.len()));
+    }
+    let count = count as usize;
+
+    let read_vec3 = |offset: usize| -> [f32; 3] {
+        let f = |i: usize| {
+            let at = offset + i * 4;
+            f32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+        };
+        [f(0), f(1), f(2)]
+    };
+
+    Ok((0..count)
+        .map(|i| {
+            let base = 84 + i * 50;
+            (
+                read_vec3(base),
+                [read_vec3(base + 12), read_vec3(base + 24), read_vec3(base + 36)],
+            )
+        })
+        .collect())
+}
+
+fn parse_ascii_stl(text: &str) -> Result<Vec<StlFacet>, String> {
+    let mut facets = Vec::new();
+    let mut normal = [0.0; 3];
+    let mut corners: Vec<[f32; 3]> = Vec::with_capacity(3);
+
+    for (line_no, line) in text.lines().enumerate() {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let line_err = |msg: &str| format!("STL line {}: {}", line_no + 1, msg);
+
+        match parts.first().copied() {
+            Some("facet") => {
+                // `facet normal nx ny nz` - shift by one so parse_vec3 sees the numbers
+                normal = parse_vec3(&parts[1..]).map_err(|e| line_err(&e))?;
+                corners.clear();
+            }
+            Some("vertex") => corners.push(parse_vec3(&parts).map_err(|e| line_err(&e))?),
+            Some("endfacet") => {
+                if corners.len() != 3 {
+                    return Err(line_err(&format!("facet has {} vertices, expected 3", corners.len())));
+                }
+                facets.push((normal, [corners[0], corners[1], corners[2]]));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(facets)
+}
+
+/// Build point cloud vertices from flat arrays: `positions` as xyz triples,
+/// `colors` as rgb or rgba per point (empty for `DEFAULT_COLOR`), and
+/// `sizes` as one size factor per point (empty for 1).
+pub fn point_vertices(positions: &[f32], colors: &[f32], sizes: &[f32]) -> Result<Vec<PointVertex>, String> {
+    if !positions.len().is_multiple_of(3) {
+        return Err(format!("Point positions must be xyz triples, got {} values", positions.len()));
+    }
+    let count = positions.len() / 3;
+    let color_components = match colors.len() {
+        0 => 0,
+        len if len == count * 3 => 3,
+        len if len == count * 4 => 4,
+        len => return Err(format!("Expected {} or {} color values for {} points, got {}", count * 3, count * 4, count, len)),
+    };
+    if !sizes.is_empty() && sizes.len() != count {
+        return Err(format!("Expected {} point sizes, got {}", count, sizes.len()));
+    }
+
+    Ok((0..count)
+        .map(|i| {
+            let color = match color_components {
+                3 => [colors[i * 3], colors[i * 3 + 1], colors[i * 3 + 2], 1.0],
+                4 => [colors[i * 4], colors[i * 4 + 1], colors[i * 4 + 2], colors[i * 4 + 3]],
+                _ => DEFAULT_COLOR,
+            };
+            PointVertex {
+                position: [positions[i * 3], positions[i * 3 + 1], positions[i * 3 + 2]],
+                size: sizes.get(i).copied().unwrap_or(1.0),
+                color,
+            }
+        })
+        .collect())
+}
+
+/// Build polylines from flat xyz positions, with optional rgb or rgba colors
+/// per point. `lengths` gives the number of points in each polyline in turn;
+/// empty means all the points form one. Polylines are numbered from 0.
+pub fn polylines(positions: &[f32], colors: &[f32], lengths: &[u32], width: f32) -> Result<Vec<Polyline>, String> {
+    if !(width.is_finite() && width > 0.0) {
+        return Err(format!("Line width must be positive, got {}", width));
+    }
+    // Points share the color and layout rules of a point cloud
+    let points = point_vertices(positions, colors, &[])?;
+    let whole = [points.len() as u32];
+    let lengths = if lengths.is_empty() { &whole[..] } else { lengths };
+    let total: u64 = lengths.iter().map(|&len| len as u64).sum();
+    if total != points.len() as u64 {
+        return Err(format!("Polyline lengths add up to {} points, got {}", total, points.len()));
+    }
+    if let Some(len) = lengths.iter().find(|&&len| len < 2) {
+        return Err(format!("Each polyline needs at least 2 points, got {}", len));
+    }
+
+    let mut start = 0;
+    Ok(lengths
+        .iter()
+        .enumerate()
+        .map(|(id, &len)| {
+            let line = &points[start..start + len as usize];
+            start += len as usize;
+            Polyline {
+                id: id as u32,
+                positions: line.iter().map(|p| p.position).collect(),
+                colors: line.iter().map(|p| p.color).collect(),
+                width,
+            }
+        })
+        .collect())
+}
+
+/// Vertex and face data read from a PLY file.
+///
+/// Faces are kept as polygons; point clouds have no faces.
+pub struct PlyData {
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Option<Vec<[f32; 3]>>,
+    pub colors: Option<Vec<[f32; 4]>>,
+    pub faces: Vec<Vec<u32>>,
+    /// Non-standard per-vertex scalar properties (e.g. `intensity`, `quality`), by name.
+    pub scalars: Vec<(String, Vec<f32>)>,
+}
+
+impl PlyData {
+    pub fn is_point_cloud(&self) -> bool {
+        self.faces.is_empty()
+    }
+
+    /// Per-vertex colors: the named scalar property mapped through a colormap,
+    /// else the file's colors, else `DEFAULT_COLOR`.
+    pub fn vertex_colors(&self, color_by: Option<&str>) -> Result<Vec<[f32; 4]>, String> {
+        if let Some(name) = color_by {
+            let (_, values) = self
+                .scalars
+                .iter()
+                .find(|(scalar, _)| scalar == name)
+                .ok_or_else(|| format!("PLY has no vertex property '{}'", name))?;
+            return Ok(colormap_scalars(values));
+        }
+        Ok(match &self.colors {
+            Some(colors) => colors.clone(),
+            None => vec![DEFAULT_COLOR; self.positions.len()],
+        })
+    }
+
+    /// The vertices as a point cloud, colored as `vertex_colors` does.
+    pub fn to_points(&self, color_by: Option<&str>) -> Result<Vec<PointVertex>, String> {
+        let colors = self.vertex_colors(color_by)?;
+        Ok(self
+            .positions
+            .iter()
+            .zip(colors)
+            .map(|(&position, color)| PointVertex { position, size: 1.0, color })
+            .collect())
+    }
+
+    /// Triangulate the faces into an expanded triangle mesh, with one face id per polygon.
+    pub fn to_mesh(&self, color_by: Option<&str>) -> Result<(Vec<Vertex>, ModelIndices), String> {
+        if self.is_point_cloud() {
+            return Err("PLY has no faces".to_string());
+        }
+        let colors = self.vertex_colors(color_by)?;
+
+        let mut triangles: Vec<[RawVertex; 3]> = Vec::new();
+        let mut face_ids = Vec::new();
+        for (face_no, face) in self.faces.iter().enumerate() {
+            if let Some(&idx) = face.iter().find(|&&idx| idx as usize >= self.positions.len()) {
+                return Err(format!(
+                    "PLY face {} references vertex {} ({} defined)",
+                    face_no,
+                    idx,
+                    self.positions.len()
+                ));
+            }
+            if face.len() < 3 {
+                continue;
+            }
+            let positions: Vec<[f32; 3]> = face.iter().map(|&idx| self.positions[idx as usize]).collect();
+            for tri in triangulate_polygon(&positions) {
+                let corners = tri.map(|corner| face[corner] as usize);
+                let face_normal = triangle_normal(
+                    self.positions[corners[0]],
+                    self.positions[corners[1]],
+                    self.positions[corners[2]],
+                );
+                triangles.push(corners.map(|idx| {
+                    let normal = self.normals.as_ref().map_or(face_normal, |normals| normals[idx]);
+                    (self.positions[idx], normal, colors[idx])
+                }));
+                face_ids.push(face_no as u32);
+            }
+        }
+
+        if triangles.is_empty() {
+            return Err("No faces found in PLY".to_string());
+        }
+        Ok(expand_faces(&triangles, face_ids))
+    }
+}
+
+/// Map scalar values onto the viridis colormap over their min..max range.
+fn colormap_scalars(values: &[f32]) -> Vec<[f32; 4]> {
+    let min = values.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let range = if max > min { max - min } else { 1.0 };
+    values
+        .iter()
+        .map(|&v| {
+            let [r, g, b] = colormap_viridis((v - min) / range);
+            [r, g, b, 1.0]
+        })
+        .collect()
+}
+
+/// Cheap quadratic approximation of viridis (matches the Python client's).
+pub fn colormap_viridis(t: f32) -> [f32; 3] {
+    let t = t.clamp(0.0, 1.0);
+    [
+        (-0.37 + 2.63 * t - 1.65 * t * t).clamp(0.0, 1.0),
+        (0.14 + 1.10 * t - 0.30 * t * t).clamp(0.0, 1.0),
+        (0.55 - 0.50 * t).clamp(0.0, 1.0),
+    ]
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum PlyScalar {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl PlyScalar {
+    fn from_name(name: &str) -> Result<Self, String> {
+        Ok(match name {
+            "char" | "int8" => PlyScalar::I8,
+            "uchar" | "uint8" => PlyScalar::U8,
+            "short" | "int16" => PlyScalar::I16,
+            "ushort" | "uint16" => PlyScalar::U16,
+            "int" | "int32" => PlyScalar::I32,
+            "uint" | "uint32" => PlyScalar::U32,
+            "float" | "float32" => PlyScalar::F32,
+            "double" | "float64" => PlyScalar::F64,
+            _ => return Err(format!("unknown PLY property type '{}'", name)),
+        })
+    }
+
+    fn size(self) -> usize {
+        match self {
+            PlyScalar::I8 | PlyScalar::U8 => 1,
+            PlyScalar::I16 | PlyScalar::U16 => 2,
+            PlyScalar::I32 | PlyScalar::U32 | PlyScalar::F32 => 4,
+            PlyScalar::F64 => 8,
+        }
+    }
+
+    /// Scale that maps an integer color channel onto 0..1.
+    fn color_scale(self) -> f32 {
+        match self {
+            PlyScalar::U8 => 1.0 / 255.0,
+            PlyScalar::U16 => 1.0 / 65535.0,
+            _ => 1.0,
+        }
+    }
+}
+
+enum PlyProperty {
+    Scalar { name: String, ty: PlyScalar },
+    List { name: String, count_ty: PlyScalar, item_ty: PlyScalar },
+}
+
+struct PlyElement {
+    name: String,
+    count: usize,
+    properties: Vec<PlyProperty>,
+}
+
+/// Reads property values from the PLY body in any of the three encodings.
+enum PlyReader<'a> {
+    Ascii(std::str::SplitAsciiWhitespace<'a>),
+    Binary { bytes: &'a [u8], pos: usize, big_endian: bool },
+}
+
+impl PlyReader<'_> {
+    fn read(&mut self, ty: PlyScalar) -> Result<f64, String> {
+        match self {
+            PlyReader::Ascii(tokens) => {
+                let token = tokens.next().ok_or("PLY body ended early")?;
+                token.parse::<f64>().map_err(|e| format!("invalid PLY value '{}': {}", token, e))
+            }
+            PlyReader::Binary { bytes, pos, big_endian } => {
+                let size = ty.size();
+                let raw = bytes.get(*pos..*pos + size).ok_or("PLY body ended early")?;
+                *pos += size;
+                let mut buf = [0u8; 8];
+                buf[..size].copy_from_slice(raw);
+                if *big_endian {
+                    buf[..size].reverse();
+                }
+                let [b0, b1, b2, b3, ..] = buf;
+                Ok(match ty {
+                    PlyScalar::I8 => b0 as i8 as f64,
+                    PlyScalar::U8 => b0 as f64,
+                    PlyScalar::I16 => i16::from_le_bytes([b0, b1]) as f64,
+                    PlyScalar::U16 => u16::from_le_bytes([b0, b1]) as f64,
+                    PlyScalar::I32 => i32::from_le_bytes([b0, b1, b2, b3]) as f64,
+                    PlyScalar::U32 => u32::from_le_bytes([b0, b1, b2, b3]) as f64,
+                    PlyScalar::F32 => f32::from_le_bytes([b0, b1, b2, b3]) as f64,
+                    PlyScalar::F64 => f64::from_le_bytes(buf),
+                })
+            }
+        }
+    }
+}
+
+/// Parse an ASCII or binary (little/big endian) PLY file.
+///
+/// The `vertex` element's `x y z`, `nx ny nz` and `red green blue alpha`
+/// properties fill positions, normals and colors; any other scalar vertex
+/// property is kept in `PlyData::scalars`. The `face` element's
+/// `vertex_indices` (or `vertex_index`) list gives the polygons. Other
+/// elements are skipped.
+pub fn parse_ply(bytes: &[u8]) -> Result<PlyData, String> {
+    let header_end = bytes
+        .windows(b"end_header".len())
+        .position(|w| w == b"end_header")
+        .ok_or("PLY header has no end_header")?;
+    let body_start = bytes[header_end..]
+        .iter()
+        .position(|&b| b == b'\n')
+        .map_or(bytes.len(), |nl| header_end + nl + 1);
+    let header = std::str::from_utf8(&bytes[..header_end]).map_err(|_| "PLY header is not ASCII")?;
+
+    let mut lines = header.lines();
+    if lines.next().map(str::trim) != Some("ply") {
+        return Err("Not a PLY file (missing 'ply' magic)".to_string());
+    }
+
+    let mut format = None;
+    let mut elements: Vec<PlyElement> = Vec::new();
+    for line in lines {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        match parts.as_slice() {
+            ["format", encoding, ..] => format = Some(encoding.to_string()),
+            ["element", name, count] => elements.push(PlyElement {
+                name: name.to_string(),
+                count: count.parse().map_err(|_| format!("invalid PLY element count '{}'", count))?,
+                properties: Vec::new(),
+            }),
+            ["property", "list", count_ty, item_ty, name] => {
+                let element = elements.last_mut().ok_or("PLY property before element")?;
+                element.properties.push(PlyProperty::List {
+                    name: name.to_string(),
+                    count_ty: PlyScalar::from_name(count_ty)?,
+                    item_ty: PlyScalar::from_name(item_ty)?,
+                });
+            }
+            ["property", ty, name] => {
+                let element = elements.last_mut().ok_or("PLY property before element")?;
+                element.properties.push(PlyProperty::Scalar {
+                    name: name.to_string(),
+                    ty: PlyScalar::from_name(ty)?,
+                });
+            }
+            _ => {} // comment, obj_info, blank
+        }
+    }
+
+    let body = &bytes[body_start..];
+    let mut reader = match format.as_deref() {
+        Some("ascii") => PlyReader::Ascii(
+            std::str::from_utf8(body).map_err(|_| "PLY ascii body is not valid text")?.split_ascii_whitespace(),
+        ),
+        Some("binary_little_endian") => PlyReader::Binary { bytes: body, pos: 0, big_endian: false },
+        Some("binary_big_endian") => PlyReader::Binary { bytes: body, pos: 0, big_endian: true },
+        Some(other) => return Err(format!("unsupported PLY format '{}'", other)),
+        None => return Err("PLY header has no format line".to_string()),
+    };
+
+    let mut data = PlyData {
+        positions: Vec::new(),
+        normals: None,
+        colors: None,
+        faces: Vec::new(),
+        scalars: Vec::new(),
+    };
+
+    for element in &elements {
+        let has = |name: &str| {
+            element
+                .properties
+                .iter()
+                .any(|p| matches!(p, PlyProperty::Scalar { name: n, .. } if n == name))
+        };
+        let is_vertex = element.name == "vertex";
+        if is_vertex {
+            if !(has("x") && has("y") && has("z")) {
+                return Err("PLY vertex element needs x, y and z".to_string());
+            }
+            // Every value takes at least a byte, so a count the body can't
+            // hold is corrupt and will fail to read; don't reserve for it
+            let capacity = element.count.min(body.len());
+            data.positions.reserve(capacity);
+            if has("nx") && has("ny") && has("nz") {
+                data.normals = Some(Vec::with_capacity(capacity));
+            }
+            if has("red") && has("green") && has("blue") {
+                data.colors = Some(Vec::with_capacity(capacity));
+            }
+            for property in &element.properties {
+                if let PlyProperty::Scalar { name, .. } = property {
+                    let standard = ["x", "y", "z", "nx", "ny", "nz", "red", "green", "blue", "alpha"];
+                    if !standard.contains(&name.as_str()) {
+                        data.scalars.push((name.clone(), Vec::with_capacity(capacity)));
+                    }
+                }
+            }
+        }
+
+        for _ in 0..element.count {
+            let mut position = [0.0; 3];
+            let mut normal = [0.0; 3];
+            let mut color = [1.0; 4];
+            let mut scalar_idx = 0;
+
+            for property in &element.properties {
+                match property {
+                    PlyProperty::Scalar { name, ty } => {
+                        let value = reader.read(*ty)?;
+                        if !is_vertex {
+                            continue;
+                        }
+                        let v = value as f32;
+                        match name.as_str() {
+                            "x" => position[0] = v,
+                            "y" => position[1] = v,
+                            "z" => position[2] = v,
+                            "nx" => normal[0] = v,
+                            "ny" => normal[1] = v,
+                            "nz" => normal[2] = v,
+                            "red" => color[0] = v * ty.color_scale(),
+                            "green" => color[1] = v * ty.color_scale(),
+                            "blue" => color[2] = v * ty.color_scale(),
+                            "alpha" => color[3] = v * ty.color_scale(),
+                            _ => {
+                                data.scalars[scalar_idx].1.push(v);
+                                scalar_idx += 1;
+                            }
+                        }
+                    }
+                    PlyProperty::List { name, count_ty, item_ty } => {
+                        let count = reader.read(*count_ty)? as usize;
+                        let mut items = Vec::with_capacity(count.min(body.len()));
+                        for _ in 0..count {
+                            items.push(reader.read(*item_ty)? as u32);
+                        }
+                        if element.name == "face" && (name == "vertex_indices" || name == "vertex_index") {
+                            data.faces.push(items);
+                        }
+                    }
+                }
+            }
+
+            if is_vertex {
+                data.positions.push(position);
+                if let Some(normals) = data.normals.as_mut() {
+                    normals.push(normal);
+                }
+                if let Some(colors) = data.colors.as_mut() {
+                    colors.push(color);
+                }
+            }
+        }
+    }
+
+    if data.positions.is_empty() {
+        return Err("No vertices found in PLY".to_string());
+    }
+
+    Ok(data)
+}
+
+/// Resolve a 1-based (or negative, relative-to-end) OBJ index into a 0-based one.
+fn resolve_obj_index(token: &str, count: usize) -> Result<usize, String> {
+    let index = token.parse::<i64>().map_err(|e| format!("invalid index '{}': {}", token, e))?;
+    let resolved = if index < 0 { count as i64 + index } else { index - 1 };
+    if index == 0 || resolved < 0 || resolved >= count as i64 {
+        return Err(format!("index {} out of range ({} defined)", index, count));
+    }
+    Ok(resolved as usize)
+}
+
+fn parse_f32(token: Option<&&str>) -> Result<f32, String> {
+    let token = token.ok_or("missing value")?;
+    token.parse::<f32>().map_err(|e| format!("invalid number '{}': {}", token, e))
+}
+
+/// Parse the three numbers following a keyword, e.g. `v 1 2 3`.
+fn parse_vec3(parts: &[&str]) -> Result<[f32; 3], String> {
+    Ok([
+        parse_f32(parts.get(1))?,
+        parse_f32(parts.get(2))?,
+        parse_f32(parts.get(3))?,
+    ])
+}
+
+/// Unit normal of a counter-clockwise triangle.
+pub fn triangle_normal(a: [f32; 3], b: [f32; 3], c: [f32; 3]) -> [f32; 3] {
+    normalize(cross(sub(b, a), sub(c, a)))
+}
+
+/// Axis-aligned bounding box and bounding sphere of a model.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bounds {
+    pub min: [f32; 3],
+    pub max: [f32; 3],
+    /// Center of the box, which is also the center of the sphere.
+    pub center: [f32; 3],
+    /// Distance from `center` to the farthest vertex (an upper bound once
+    /// transformed or merged).
+    pub radius: f32,
+}
+
+impl Bounds {
+    /// Bounds after applying `transform`: the box around the eight transformed
+    /// corners, and the sphere grown by the transform's largest axis scale.
+    pub fn transformed(&self, transform: &Mat4) -> Bounds {
+        let mut min = [f32::MAX; 3];
+        let mut max = [f32::MIN; 3];
+        for corner in 0..8 {
+            let p = [0, 1, 2].map(|axis| if corner & (1 << axis) == 0 { self.min[axis] } else { self.max[axis] });
+            let p = transform_point(p, transform);
+            for axis in 0..3 {
+                min[axis] = min[axis].min(p[axis]);
+                max[axis] = max[axis].max(p[axis]);
+            }
+        }
+        let max_scale = transform[..3]
+            .iter()
+            .map(|col| dot([col[0], col[1], col[2]], [col[0], col[1], col[2]]).sqrt())
+            .fold(0.0f32, f32::max);
+        Bounds::enclosing(min, max, &[(transform_point(self.center, transform), self.radius * max_scale)])
+    }
+
+    /// Bounds enclosing both boxes and both spheres.
+    pub fn union(&self, other: &Bounds) -> Bounds {
+        let min = [0, 1, 2].map(|axis| self.min[axis].min(other.min[axis]));
+        let max = [0, 1, 2].map(|axis| self.max[axis].max(other.max[axis]));
+        Bounds::enclosing(min, max, &[(self.center, self.radius), (other.center, other.radius)])
+    }
+
+    /// Box `min`..`max` with a sphere about its center that contains every
+    /// `(center, radius)` sphere given.
+    fn enclosing(min: [f32; 3], max: [f32; 3], spheres: &[([f32; 3], f32)]) -> Bounds {
+        let center = [0, 1, 2].map(|axis| (min[axis] + max[axis]) * 0.5);
+        let radius = spheres
+            .iter()
+            .map(|&(c, r)| dot(sub(c, center), sub(c, center)).sqrt() + r)
+            .fold(0.0f32, f32::max);
+        Bounds { min, max, center, radius }
+    }
+}
+
+/// Bounds of all finite points (e.g. flattened triangle corners), or `None`
+/// if there are none.
+pub fn compute_bounds(points: &[[f32; 3]]) -> Option<Bounds> {
+    let points = || points.iter().filter(|p| p.iter().all(|c| c.is_finite()));
+    let mut min = [f32::MAX; 3];
+    let mut max = [f32::MIN; 3];
+    for p in points() {
+        for axis in 0..3 {
+            min[axis] = min[axis].min(p[axis]);
+            max[axis] = max[axis].max(p[axis]);
+        }
+    }
+    if min[0] > max[0] {
+        return None;
+    }
+    let center = [0, 1, 2].map(|axis| (min[axis] + max[axis]) * 0.5);
+    let radius = points()
+        .map(|&p| dot(sub(p, center), sub(p, center)))
+        .fold(0.0f32, f32::max)
+        .sqrt();
+    Some(Bounds { min, max, center, radius })
+}
+
+/// Face id of each triangle in expanded geometry, read from its first vertex.
+pub fn extract_face_ids(vertices: &[Vertex], indices: &ModelIndices) -> Vec<u32> {
+    (0..indices.len() / 3).map(|t| vertices[indices.get(t * 3)].face_id).collect()
 }
 
 /// Extract triangle positions from vertices and indices for picking.
-pub fn extract_triangles(vertices: &[Vertex], indices: &[u16]) -> Vec<[[f32; 3]; 3]> {
+pub fn extract_triangles(vertices: &[Vertex], indices: &ModelIndices) -> Vec<[[f32; 3]; 3]> {
     let mut triangles = Vec::new();
-    for chunk in indices.chunks(3) {
-        if chunk.len() == 3 {
-            triangles.push([
-                vertices[chunk[0] as usize].position,
-                vertices[chunk[1] as usize].position,
-                vertices[chunk[2] as usize].position,
-            ]);
-        }
+    for i in (0..indices.len() / 3).map(|t| t * 3) {
+        triangles.push([
+            vertices[indices.get(i)].position,
+            vertices[indices.get(i + 1)].position,
+            vertices[indices.get(i + 2)].position,
+        ]);
     }
     triangles
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn obj_concave_polygon_is_one_face_covering_its_area() {
+        // An L shape of area 3 starting next to its reflex corner, where a fan
+        // from the first corner would reach across the missing square
+        let obj = "v 0 0 0\nv 2 0 0\nv 2 1 0\nv 1 1 0\nv 1 2 0\nv 0 2 0\nf 3 4 5 6 1 2\n";
+        let (vertices, indices) = parse_obj(obj, None).unwrap();
+        assert!(extract_face_ids(&vertices, &indices).iter().all(|&id| id == 0));
+        let area: f32 = extract_triangles(&vertices, &indices)
+            .iter()
+            .map(|[a, b, c]| ((b[0] - a[0]) * (c[1] - a[1]) - (c[0] - a[0]) * (b[1] - a[1])).abs() * 0.5)
+            .sum();
+        assert!((area - 3.0).abs() < 1e-5, "triangles cover {} instead of 3", area);
+    }
+
+    #[test]
+    fn ply_concave_polygon_is_one_face_covering_its_area() {
+        // The L shape from the OBJ test, as one PLY face
+        let ply = "ply\nformat ascii 1.0\nelement vertex 6\nproperty float x\nproperty float y\nproperty float z\n\
+                   element face 1\nproperty list uchar int vertex_indices\nend_header\n\
+                   0 0 0\n2 0 0\n2 1 0\n1 1 0\n1 2 0\n0 2 0\n6 2 3 4 5 0 1\n";
+        let (vertices, indices) = parse_ply(ply.as_bytes()).unwrap().to_mesh(None).unwrap();
+        assert!(extract_face_ids(&vertices, &indices).iter().all(|&id| id == 0));
+        let area: f32 = extract_triangles(&vertices, &indices)
+            .iter()
+            .map(|[a, b, c]| ((b[0] - a[0]) * (c[1] - a[1]) - (c[0] - a[0]) * (b[1] - a[1])).abs() * 0.5)
+            .sum();
+        assert!((area - 3.0).abs() < 1e-5, "triangles cover {} instead of 3", area);
+    }
+
+    #[test]
+    fn binary_stl_with_huge_facet_count_is_truncated_not_a_panic() {
+        let mut bytes = vec![0u8; 84 + 50];
+        bytes[80..84].copy_from_slice(&u32::MAX.to_le_bytes());
+        let Err(error) = parse_stl(&bytes, DEFAULT_COLOR) else {
+            panic!("corrupt STL was accepted");
+        };
+        assert!(error.contains("truncated"), "{}", error);
+    }
+
+    #[test]
+    fn ply_with_huge_counts_is_an_error_not_an_abort() {
+        let header = "ply\nformat binary_little_endian 1.0\nelement vertex 4000000000000000000\n\
+                      property float x\nproperty float y\nproperty float z\nend_header\n";
+        let mut bytes = header.as_bytes().to_vec();
+        bytes.extend_from_slice(&[0u8; 12]);
+        assert!(parse_ply(&bytes).is_err());
+
+        let header = "ply\nformat binary_little_endian 1.0\nelement vertex 3\n\
+                      property float x\nproperty float y\nproperty float z\n\
+                      element face 1\nproperty list uint uint vertex_indices\nend_header\n";
+        let mut bytes = header.as_bytes().to_vec();
+        bytes.extend_from_slice(&[0u8; 36]);
+        bytes.extend_from_slice(&u32::MAX.to_le_bytes());
+        assert!(parse_ply(&bytes).is_err());
+    }
+
+    #[test]
+    fn model_errors_report_their_kind_and_position() {
+        let triangle = "v 0 0 0\nv 1 0 0\nv 0 1 0\n";
+        let cases = [
+            (format!("{}f 0 x 2\n", triangle), "bad_token", 4, 5),
+            ("v 0 0\n".to_string(), "missing_fields", 1, 1),
+            (format!("{}f 0 1 3\n", triangle), "index_out_of_range", 4, 7),
+            (format!("{}f 0 1 1\n", triangle), "degenerate_face", 4, 7),
+            ("v 0 NaN 0\n".to_string(), "nan_coordinate", 1, 5),
+            (triangle.to_string(), "empty", 3, 1),
+        ];
+        for (text, kind, line, column) in cases {
+            let Err(error) = parse_model(&text) else {
+                panic!("{:?} parsed", text);
+            };
+            assert_eq!((error.kind(), error.line(), error.column()), (kind, line, column), "{}", error);
+        }
+    }
+
+    #[test]
+    fn polygons_keep_one_face_id_each() {
+        let text = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nv 2 0 0\nf 0 1 2 3\nf 1 4 2\n";
+        let mesh = parse_model(text).unwrap();
+        assert_eq!(mesh.face_ids.to_vec(), [0, 0, 1]);
+    }
+
+    #[test]
+    fn vertex_with_too_many_values_is_an_error() {
+        let text = "v 0 0 0 0 0 1 1 0 0 1 7\nv 1 0 0\nv 0 1 0\nf 1 2 3\n";
+        let Err(error) = parse_model(text) else {
+            panic!("extra vertex value was ignored");
+        };
+        assert!(matches!(error, ModelError::MissingFields { line: 1, found: 11, .. }), "{}", error);
+    }
+}
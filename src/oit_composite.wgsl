@@ -0,0 +1,27 @@
+// Weighted blended order-independent transparency, composite pass: blend
+// the average color of the transparent fragments accumulated by
+// `fs_oit_accum` over the opaque scene, by their combined coverage.
+
+@group(0) @binding(0)
+var accum_texture: texture_2d<f32>;
+@group(0) @binding(1)
+var revealage_texture: texture_2d<f32>;
+
+// Fullscreen triangle, no vertex buffer
+@vertex
+fn vs_fullscreen(@builtin(vertex_index) vertex_index: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((vertex_index << 1u) & 2u), f32(vertex_index & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+@fragment
+fn fs_composite(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let coord = vec2<i32>(position.xy);
+    let revealage = textureLoad(revealage_texture, coord, 0).r;
+    if (revealage >= 0.999) {
+        discard; // No transparent fragments here
+    }
+    let accum = textureLoad(accum_texture, coord, 0);
+    let average = accum.rgb / clamp(accum.a, 1e-4, 5e4);
+    return vec4<f32>(average, 1.0 - revealage);
+}
@@ -1,11 +1,15 @@
-/// Ray-triangle intersection and face picking.
+/// Ray casting against triangles, polylines and points for picking.
 
-use crate::math::{cross, dot, mat4_mul, mat4_rotate_x, mat4_rotate_y, mat4_scale, normalize, sub, transform_point};
-use crate::state::InteractionState;
+use std::collections::HashMap;
+
+use crate::math::{cross, dot, mat4_determinant3, mat4_mul, normalize, sub, transform_point};
+use crate::state::{InteractionState, PointSizeMode, Projection};
 
 // Camera constants (must match renderer)
 pub const FIELD_OF_VIEW_DEG: f32 = 45.0;
-pub const CAMERA_POSITION: [f32; 3] = [0.0, 0.0, 3.0];
+// Smallest radius points and lines are picked within, in CSS pixels, so tiny
+// points and thin lines can still be clicked
+const MIN_PICK_RADIUS: f32 = 3.0;
 
 /// Möller–Trumbore ray-triangle intersection algorithm.
 /// Returns the distance along the ray if intersection occurs.
@@ -52,60 +56,160 @@ fn ray_triangle_intersect(
 }
 
 /// Convert screen coordinates to a ray in view space.
-fn screen_to_ray(
-    x: f32,
-    y: f32,
-    width: u32,
-    height: u32,
-) -> ([f32; 3], [f32; 3]) {
-    let aspect = width as f32 / height as f32;
-    let fov = FIELD_OF_VIEW_DEG.to_radians();
-    let tan_fov = (fov / 2.0).tan();
+fn screen_to_ray(x: f32, y: f32, state: &InteractionState) -> ([f32; 3], [f32; 3]) {
+    let (width, height) = (state.canvas_width as f32, state.canvas_height as f32);
+    let aspect = width / height;
 
     // Convert screen coords to normalized device coords (-1 to 1)
-    let ndc_x = (2.0 * x / width as f32) - 1.0;
-    let ndc_y = 1.0 - (2.0 * y / height as f32); // Flip Y
+    let ndc_x = (2.0 * x / width) - 1.0;
+    let ndc_y = 1.0 - (2.0 * y / height); // Flip Y
+
+    match state.projection {
+        Projection::Perspective => {
+            let tan_fov = (FIELD_OF_VIEW_DEG.to_radians() / 2.0).tan();
+            let ray_dir = normalize([ndc_x * aspect * tan_fov, ndc_y * tan_fov, -1.0]);
+            (state.camera_position(), ray_dir)
+        }
+        Projection::Orthographic => {
+            // Parallel rays, starting from the point on the camera plane under the cursor
+            let half_height = state.visible_half_height();
+            let camera = state.camera_position();
+            let origin = [
+                camera[0] + ndc_x * aspect * half_height,
+                camera[1] + ndc_y * half_height,
+                camera[2],
+            ];
+            (origin, [0.0, 0.0, -1.0])
+        }
+    }
+}
 
-    // Convert to view space ray direction
-    let ray_dir = normalize([
-        ndc_x * aspect * tan_fov,
-        ndc_y * tan_fov,
-        -1.0,
-    ]);
+/// Size of a CSS pixel in view-space units at `depth` in front of the camera.
+fn pixel_size_at(depth: f32, state: &InteractionState) -> f32 {
+    let half_height = match state.projection {
+        Projection::Perspective => depth * (FIELD_OF_VIEW_DEG.to_radians() / 2.0).tan(),
+        Projection::Orthographic => state.visible_half_height(),
+    };
+    2.0 * half_height / state.canvas_height.max(1) as f32
+}
 
-    (CAMERA_POSITION, ray_dir)
+/// Closest approach of a ray (with unit direction) to the segment `a`-`b`:
+/// the distance along the ray and how far the segment is from it there.
+fn ray_segment_distance(origin: [f32; 3], dir: [f32; 3], a: [f32; 3], b: [f32; 3]) -> (f32, f32) {
+    let along = sub(b, a);
+    let to_origin = sub(origin, a);
+    let (b_dot, c, d, e) = (dot(dir, along), dot(along, along), dot(dir, to_origin), dot(along, to_origin));
+    let denom = c - b_dot * b_dot;
+    // Parallel (or zero-length) segments are measured from their start
+    let s = if denom > f32::EPSILON * c { ((e - b_dot * d) / denom).clamp(0.0, 1.0) } else { 0.0 };
+    let closest = [a[0] + s * along[0], a[1] + s * along[1], a[2] + s * along[2]];
+    let offset = sub(closest, origin);
+    let t = dot(offset, dir);
+    (t, (dot(offset, offset) - t * t).max(0.0).sqrt())
 }
 
-/// Pick a face given screen coordinates. Returns -1 if no face hit.
-pub fn pick_face(x: f32, y: f32, state: &InteractionState) -> i32 {
-    let (ray_origin, ray_view_dir) = screen_to_ray(
-        x, y,
-        state.canvas_width,
-        state.canvas_height,
-    );
+/// Pick the nearest face, polyline, or point of a point cloud, under screen
+/// coordinates across all visible objects.
+///
+/// Returns `(object id, face id)`, with the polyline's id as the face id for
+/// lines and the point index for point clouds, or `None` if nothing was hit.
+pub fn pick_face(x: f32, y: f32, state: &InteractionState) -> Option<(u32, u32)> {
+    let (ray_origin, ray_view_dir) = screen_to_ray(x, y, state);
 
     // Build model matrix to transform triangles
-    let model_mat = mat4_mul(
-        mat4_mul(mat4_scale(state.scale), mat4_rotate_x(state.rotation_x)),
-        mat4_rotate_y(state.rotation_y)
-    );
+    let model_mat = state.model_matrix();
 
-    let mut closest_face: i32 = -1;
+    let mut closest = None;
     let mut closest_t = f32::MAX;
 
-    for (face_id, tri) in state.model_triangles.iter().enumerate() {
-        // Transform triangle vertices by model matrix
-        let v0 = transform_point(tri[0], &model_mat);
-        let v1 = transform_point(tri[1], &model_mat);
-        let v2 = transform_point(tri[2], &model_mat);
+    for object in state.objects.iter().filter(|object| object.visible) {
+        let object_mat = mat4_mul(object.transform, model_mat);
+        for (tri_idx, tri) in object.triangles.iter().enumerate() {
+            // Transform triangle vertices by the object's and the view's model matrices
+            let v0 = transform_point(tri[0], &object_mat);
+            let v1 = transform_point(tri[1], &object_mat);
+            let v2 = transform_point(tri[2], &object_mat);
+
+            if let Some(t) = ray_triangle_intersect(ray_origin, ray_view_dir, v0, v1, v2) {
+                if t < closest_t {
+                    closest_t = t;
+                    let face = object.face_ids.get(tri_idx).copied().unwrap_or(tri_idx as u32);
+                    closest = Some((object.id, face));
+                }
+            }
+        }
 
-        if let Some(t) = ray_triangle_intersect(ray_origin, ray_view_dir, v0, v1, v2) {
-            if t < closest_t {
+        // Lines are hit where the ray passes within half their width of a segment
+        for line in &object.lines {
+            let positions: Vec<[f32; 3]> = line.positions.iter().map(|&p| transform_point(p, &object_mat)).collect();
+            for segment in positions.windows(2) {
+                let (t, distance) = ray_segment_distance(ray_origin, ray_view_dir, segment[0], segment[1]);
+                if t <= 0.0 || t >= closest_t {
+                    continue;
+                }
+                let radius = (line.width * 0.5).max(MIN_PICK_RADIUS) * pixel_size_at(-t * ray_view_dir[2], state);
+                if distance <= radius {
+                    closest_t = t;
+                    closest = Some((object.id, line.id));
+                }
+            }
+        }
+
+        // Points are hit where the ray passes within their splat's radius
+        let unit_scale = mat4_determinant3(&object_mat).abs().cbrt();
+        for (index, &point) in object.points.iter().enumerate() {
+            let p = transform_point(point, &object_mat);
+            let offset = sub(p, ray_origin);
+            let t = dot(offset, ray_view_dir);
+            if t <= 0.0 || t >= closest_t {
+                continue;
+            }
+            let diameter = state.point_size * object.point_sizes.get(index).copied().unwrap_or(1.0);
+            let pixel_size = pixel_size_at(ray_origin[2] - p[2], state);
+            let radius = match state.point_size_mode {
+                PointSizeMode::Screen => diameter * 0.5 * pixel_size,
+                PointSizeMode::World => diameter * 0.5 * unit_scale,
+            }
+            .max(MIN_PICK_RADIUS * pixel_size);
+            if dot(offset, offset) - t * t <= radius * radius {
                 closest_t = t;
-                closest_face = face_id as i32;
+                closest = Some((object.id, index as u32));
             }
         }
     }
 
-    closest_face
+    closest
+}
+
+/// An undirected edge: its endpoints' bit patterns, lower first.
+type EdgeKey = ([u32; 3], [u32; 3]);
+/// An edge's endpoints as first seen, and how many triangles share it.
+type EdgeEntry = ([f32; 3], [f32; 3], u32);
+
+/// Outline of every triangle with the given face id, as a line list.
+///
+/// Edges shared by two of the face's triangles are interior diagonals of a
+/// triangulated polygon and are left out, so a quad outlines as four edges.
+pub fn face_outline(triangles: &[[[f32; 3]; 3]], face_ids: &[u32], face: u32) -> Vec<[f32; 3]> {
+    let key = |p: [f32; 3]| p.map(f32::to_bits);
+    let mut edges: HashMap<EdgeKey, EdgeEntry> = HashMap::new();
+    let mut order = Vec::new();
+    for (tri, _) in triangles.iter().zip(face_ids).filter(|&(_, &id)| id == face) {
+        for i in 0..3 {
+            let (a, b) = (tri[i], tri[(i + 1) % 3]);
+            let edge_key = if key(a) < key(b) { (key(a), key(b)) } else { (key(b), key(a)) };
+            edges.entry(edge_key).or_insert_with(|| {
+                order.push(edge_key);
+                (a, b, 0)
+            }).2 += 1;
+        }
+    }
+    order
+        .iter()
+        .filter_map(|edge_key| match edges[edge_key] {
+            (a, b, 1) => Some([a, b]),
+            _ => None,
+        })
+        .flatten()
+        .collect()
 }
@@ -0,0 +1,74 @@
+//! Minimal PNG encoder.
+//!
+//! Writes 8-bit RGBA images with no filtering, deflated by `miniz_oxide`.
+
+// miniz's default level: most of the size win of level 9 at a fraction of the time
+const COMPRESSION_LEVEL: u8 = 6;
+
+/// Encode `rgba` (rows top to bottom, 4 bytes per pixel) as a PNG file.
+pub fn encode_rgba(width: u32, height: u32, rgba: &[u8]) -> Vec<u8> {
+    assert_eq!(rgba.len(), width as usize * height as usize * 4, "RGBA data does not match image size");
+
+    // Each scanline is prefixed with its filter type (0: none)
+    let row_len = width as usize * 4;
+    let mut raw = Vec::with_capacity((row_len + 1) * height as usize);
+    for row in rgba.chunks_exact(row_len.max(1)).take(height as usize) {
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+
+    let zlib = miniz_oxide::deflate::compress_to_vec_zlib(&raw, COMPRESSION_LEVEL);
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    // Bit depth 8, color type 6 (RGBA), default compression, filtering and no interlace
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+    let mut png = Vec::with_capacity(zlib.len() + 57);
+    png.extend_from_slice(b"\x89PNG\r\n\x1a\n");
+    write_chunk(&mut png, b"IHDR", &header);
+    write_chunk(&mut png, b"IDAT", &zlib);
+    write_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let table: [u32; 256] = std::array::from_fn(|n| {
+        (0..8).fold(n as u32, |c, _| if c & 1 != 0 { 0xedb8_8320 ^ (c >> 1) } else { c >> 1 })
+    });
+    !bytes
+        .iter()
+        .fold(!0u32, |crc, &b| table[((crc ^ b as u32) & 0xff) as usize] ^ (crc >> 8))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn idat_is_compressed_and_inflates_to_the_filtered_rows() {
+        let (width, height) = (64, 32);
+        let rgba = vec![200u8; width * height * 4];
+        let png = encode_rgba(width as u32, height as u32, &rgba);
+
+        // IHDR chunk is 8 + 4 + 4 + 13 + 4 bytes after the 8-byte signature
+        let idat = &png[8 + 25..];
+        assert_eq!(&idat[4..8], b"IDAT");
+        let len = u32::from_be_bytes([idat[0], idat[1], idat[2], idat[3]]) as usize;
+        assert!(len < rgba.len() / 10, "IDAT is {} bytes for {} of pixels", len, rgba.len());
+
+        let raw = miniz_oxide::inflate::decompress_to_vec_zlib(&idat[8..8 + len]).unwrap();
+        assert_eq!(raw.len(), (width * 4 + 1) * height);
+        assert!(raw.chunks_exact(width * 4 + 1).all(|row| row[0] == 0 && row[1..] == rgba[..width * 4]));
+    }
+}
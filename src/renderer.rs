@@ -5,25 +5,32 @@ use std::rc::Rc;
 use wasm_bindgen::prelude::*;
 use wgpu::util::DeviceExt;
 
-use crate::math::{mat4_look_at, mat4_mul, mat4_perspective, mat4_rotate_x, mat4_rotate_y, mat4_scale, mat4_to_array, normalize};
-use crate::picking::{CAMERA_POSITION, FIELD_OF_VIEW_DEG};
-use crate::state::{InteractionState, ModelResources};
-use crate::vertex::{Vertex, WireframeVertex};
+use crate::math::{mat4_from_quat, mat4_look_at, mat4_mul, mat4_normal_matrix, mat4_to_array, normalize, Mat4};
+use crate::model::{reorder_triangles, ModelIndices, Polyline, SharedMesh};
+use crate::sorting::DepthSort;
+use crate::state::{
+    InteractionState, LineCap, LineGeometry, LineJoin, ModelGeometry, ModelResources, ObjectResources, OitTargets,
+    PointSizeMode, RenderTarget, SurfaceResources, Transparency,
+};
+use crate::vertex::{GlyphInstance, LineSegment, PointVertex, Vertex, WireframeVertex};
+use crate::{axes, gizmo, glyphs};
 
 // Rendering constants
-const NEAR_PLANE: f32 = 0.1;
-const FAR_PLANE: f32 = 100.0;
-const BACKGROUND_COLOR: wgpu::Color = wgpu::Color { r: 0.1, g: 0.1, b: 0.15, a: 1.0 };
+pub const BACKGROUND_COLOR: wgpu::Color = wgpu::Color { r: 0.1, g: 0.1, b: 0.15, a: 1.0 };
 const LIGHT_DIRECTION: [f32; 3] = [1.0, 1.0, 1.0];
 pub const MSAA_SAMPLE_COUNT: u32 = 4;
+// Weighted blended transparency targets: summed weighted color, and the
+// product of transmittances (cleared to 1, fully revealed)
+const OIT_ACCUM_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+const OIT_REVEALAGE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R8Unorm;
 
-/// Create MSAA and depth textures for rendering.
-pub fn create_textures(
+/// Create the MSAA and depth attachments for drawing at a size in physical pixels.
+pub fn create_render_target(
     device: &wgpu::Device,
     width: u32,
     height: u32,
-    surface_format: wgpu::TextureFormat,
-) -> (wgpu::TextureView, wgpu::TextureView) {
+    format: wgpu::TextureFormat,
+) -> RenderTarget {
     let msaa_texture = device.create_texture(&wgpu::TextureDescriptor {
         label: Some("MSAA Texture"),
         size: wgpu::Extent3d {
@@ -34,7 +41,7 @@ pub fn create_textures(
         mip_level_count: 1,
         sample_count: MSAA_SAMPLE_COUNT,
         dimension: wgpu::TextureDimension::D2,
-        format: surface_format,
+        format,
         usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
         view_formats: &[],
     });
@@ -56,7 +63,197 @@ pub fn create_textures(
     });
     let depth_view = depth_texture.create_view(&wgpu::TextureViewDescriptor::default());
 
-    (msaa_view, depth_view)
+    RenderTarget {
+        width,
+        height,
+        msaa_view,
+        depth_view,
+        oit_targets: None,
+    }
+}
+
+/// Reconfigure the surface and recreate the MSAA/depth attachments at a new
+/// size in physical pixels.
+pub fn resize_surface(device: &wgpu::Device, surface: &mut SurfaceResources, width: u32, height: u32) {
+    let max_dimension = device.limits().max_texture_dimension_2d;
+    let width = width.clamp(1, max_dimension);
+    let height = height.clamp(1, max_dimension);
+
+    surface.canvas.set_width(width);
+    surface.canvas.set_height(height);
+    surface.config.width = width;
+    surface.config.height = height;
+    surface.surface.configure(device, &surface.config);
+    surface.target = create_render_target(device, width, height, surface.config.format);
+}
+
+/// Create a texture for drawing a frame offscreen and copying it back out.
+pub fn create_offscreen_texture(
+    device: &wgpu::Device,
+    width: u32,
+    height: u32,
+    format: wgpu::TextureFormat,
+) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Offscreen Texture"),
+        size: wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    })
+}
+
+/// A frame copied out of a texture into a buffer that can be mapped for reading.
+pub struct Readback {
+    pub buffer: wgpu::Buffer,
+    width: u32,
+    height: u32,
+    // Texture copies pad each row to a multiple of 256 bytes
+    padded_row_bytes: u32,
+    format: wgpu::TextureFormat,
+}
+
+impl Readback {
+    /// Record a copy of `texture`, an 8-bit RGBA or BGRA texture, into a new buffer.
+    pub fn new(
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        texture: &wgpu::Texture,
+    ) -> Result<Self, String> {
+        let format = texture.format();
+        if !matches!(
+            format,
+            wgpu::TextureFormat::Rgba8Unorm
+                | wgpu::TextureFormat::Rgba8UnormSrgb
+                | wgpu::TextureFormat::Bgra8Unorm
+                | wgpu::TextureFormat::Bgra8UnormSrgb
+        ) {
+            return Err(format!("Cannot read back {:?} textures", format));
+        }
+        let (width, height) = (texture.width(), texture.height());
+        let padded_row_bytes = (width * 4).next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Readback Buffer"),
+            size: padded_row_bytes as u64 * height as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::TexelCopyBufferInfo {
+                buffer: &buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row_bytes),
+                    rows_per_image: Some(height),
+                },
+            },
+            texture.size(),
+        );
+
+        Ok(Readback {
+            buffer,
+            width,
+            height,
+            padded_row_bytes,
+            format,
+        })
+    }
+
+    /// The mapped frame as RGBA bytes, rows top to bottom.
+    ///
+    /// Drawn over a transparent background, blended colors come out
+    /// premultiplied by alpha; `straight_alpha` divides it back out, as
+    /// image files expect.
+    pub fn rgba(&self, straight_alpha: bool) -> Vec<u8> {
+        let row_bytes = self.width as usize * 4;
+        let mut pixels = Vec::with_capacity(row_bytes * self.height as usize);
+        let padded = self.buffer.slice(..).get_mapped_range();
+        for row in padded.chunks_exact(self.padded_row_bytes as usize) {
+            pixels.extend_from_slice(&row[..row_bytes]);
+        }
+
+        let bgra = matches!(self.format, wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb);
+        let srgb = self.format.is_srgb();
+        for pixel in pixels.chunks_exact_mut(4) {
+            if bgra {
+                pixel.swap(0, 2);
+            }
+            let alpha = pixel[3];
+            if straight_alpha && alpha > 0 && alpha < 255 {
+                let alpha = alpha as f32 / 255.0;
+                for channel in &mut pixel[..3] {
+                    // Blending happened on linear values, so undo it on them
+                    let value = *channel as f32 / 255.0;
+                    let value = if srgb { srgb_encode(srgb_decode(value) / alpha) } else { value / alpha };
+                    *channel = (value.min(1.0) * 255.0).round() as u8;
+                }
+            }
+        }
+        pixels
+    }
+}
+
+fn srgb_decode(value: f32) -> f32 {
+    if value <= 0.04045 { value / 12.92 } else { ((value + 0.055) / 1.055).powf(2.4) }
+}
+
+fn srgb_encode(value: f32) -> f32 {
+    if value <= 0.003_130_8 { value * 12.92 } else { 1.055 * value.powf(1.0 / 2.4) - 0.055 }
+}
+
+/// Create the weighted blended transparency targets at a size in physical pixels.
+pub fn create_oit_targets(device: &wgpu::Device, layout: &wgpu::BindGroupLayout, width: u32, height: u32) -> OitTargets {
+    let create_view = |label, format, sample_count| {
+        device
+            .create_texture(&wgpu::TextureDescriptor {
+                label: Some(label),
+                size: wgpu::Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage: if sample_count > 1 {
+                    wgpu::TextureUsages::RENDER_ATTACHMENT
+                } else {
+                    wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING
+                },
+                view_formats: &[],
+            })
+            .create_view(&wgpu::TextureViewDescriptor::default())
+    };
+    let accum_msaa_view = create_view("OIT Accumulation MSAA Texture", OIT_ACCUM_FORMAT, MSAA_SAMPLE_COUNT);
+    let accum_view = create_view("OIT Accumulation Texture", OIT_ACCUM_FORMAT, 1);
+    let revealage_msaa_view = create_view("OIT Revealage MSAA Texture", OIT_REVEALAGE_FORMAT, MSAA_SAMPLE_COUNT);
+    let revealage_view = create_view("OIT Revealage Texture", OIT_REVEALAGE_FORMAT, 1);
+
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("OIT Composite Bind Group"),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(&accum_view) },
+            wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::TextureView(&revealage_view) },
+        ],
+    });
+
+    OitTargets {
+        accum_msaa_view,
+        accum_view,
+        revealage_msaa_view,
+        revealage_view,
+        bind_group,
+    }
 }
 
 /// Create the main render pipeline, transparent pipeline, and wireframe pipeline.
@@ -120,8 +317,9 @@ pub fn create_pipelines(
         cache: None,
     });
 
-    // Transparent variant: depth test reads but never writes, so back-to-front
-    // sorted alpha-blended geometry (voxels) composites correctly through itself.
+    // Transparent variant: depth test reads but never writes, so alpha-blended
+    // geometry (voxels), kept back-to-front by `DepthSort`, composites correctly
+    // through itself.
     let transparent_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
         label: Some("Transparent Render Pipeline"),
         layout: Some(&pipeline_layout),
@@ -213,12 +411,629 @@ pub fn create_pipelines(
     (render_pipeline, transparent_pipeline, wireframe_pipeline)
 }
 
-/// Create bind group layout and bind group for uniforms.
-pub fn create_bind_group(
+/// Create the opaque and transparent pipelines for shared-vertex meshes.
+///
+/// Same state as the main pipelines, but vertices come from the storage
+/// buffers in bind group 1 via `vs_shared` instead of a vertex buffer.
+pub fn create_shared_pipelines(
+    device: &wgpu::Device,
+    surface_format: wgpu::TextureFormat,
+    bind_group_layout: &wgpu::BindGroupLayout,
+    shared_mesh_layout: &wgpu::BindGroupLayout,
+) -> (wgpu::RenderPipeline, wgpu::RenderPipeline) {
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Shader"),
+        source: wgpu::ShaderSource::Wgsl(include_str!("shader.wgsl").into()),
+    });
+
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Shared Mesh Pipeline Layout"),
+        bind_group_layouts: &[bind_group_layout, shared_mesh_layout],
+        push_constant_ranges: &[],
+    });
+
+    let create = |label: &str, depth_write_enabled: bool| {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(label),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_shared"),
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: surface_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: Some(wgpu::Face::Back),
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth24Plus,
+                depth_write_enabled,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: MSAA_SAMPLE_COUNT,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+            cache: None,
+        })
+    };
+
+    (
+        create("Shared Mesh Render Pipeline", true),
+        create("Shared Mesh Transparent Pipeline", false),
+    )
+}
+
+/// Create the pipeline for point clouds.
+///
+/// Each instance is a point drawn as a 4-vertex triangle strip. Points are
+/// opaque; alpha to coverage antialiases the edges of the round splats and
+/// turns alpha below 1 into a screen-door pattern that needs no sorting.
+pub fn create_point_pipeline(
+    device: &wgpu::Device,
+    surface_format: wgpu::TextureFormat,
+    bind_group_layout: &wgpu::BindGroupLayout,
+) -> wgpu::RenderPipeline {
+    create_instanced_pipeline(
+        device,
+        surface_format,
+        bind_group_layout,
+        "Point Pipeline",
+        ("vs_points", "fs_points"),
+        PointVertex::desc(),
+    )
+}
+
+/// Create the pipeline for polylines.
+///
+/// Each instance is a line segment drawn as a 4-vertex triangle strip,
+/// opaque and antialiased with alpha to coverage like point clouds.
+pub fn create_line_pipeline(
+    device: &wgpu::Device,
+    surface_format: wgpu::TextureFormat,
+    bind_group_layout: &wgpu::BindGroupLayout,
+) -> wgpu::RenderPipeline {
+    create_instanced_pipeline(
+        device,
+        surface_format,
+        bind_group_layout,
+        "Line Pipeline",
+        ("vs_lines", "fs_lines"),
+        LineSegment::desc(),
+    )
+}
+
+/// Create an opaque, alpha-to-coverage pipeline drawing a triangle strip
+/// per instance, with the given vertex and fragment entry points.
+fn create_instanced_pipeline(
+    device: &wgpu::Device,
+    surface_format: wgpu::TextureFormat,
+    bind_group_layout: &wgpu::BindGroupLayout,
+    label: &str,
+    (vertex_entry, fragment_entry): (&str, &str),
+    instance_layout: wgpu::VertexBufferLayout,
+) -> wgpu::RenderPipeline {
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Shader"),
+        source: wgpu::ShaderSource::Wgsl(include_str!("shader.wgsl").into()),
+    });
+
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Pipeline Layout"),
+        bind_group_layouts: &[bind_group_layout],
+        push_constant_ranges: &[],
+    });
+
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some(label),
+        layout: Some(&pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: &shader,
+            entry_point: Some(vertex_entry),
+            buffers: &[instance_layout],
+            compilation_options: Default::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader,
+            entry_point: Some(fragment_entry),
+            targets: &[Some(wgpu::ColorTargetState {
+                format: surface_format,
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: Default::default(),
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleStrip,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: None,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            unclipped_depth: false,
+            conservative: false,
+        },
+        depth_stencil: Some(wgpu::DepthStencilState {
+            format: wgpu::TextureFormat::Depth24Plus,
+            depth_write_enabled: true,
+            depth_compare: wgpu::CompareFunction::Less,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        }),
+        multisample: wgpu::MultisampleState {
+            count: MSAA_SAMPLE_COUNT,
+            mask: !0,
+            alpha_to_coverage_enabled: true,
+        },
+        multiview: None,
+        cache: None,
+    })
+}
+
+/// GPU resources for drawing the axes and the orientation gizmo, which
+/// belong to no scene object.
+pub struct AxesResources {
+    pub label_pipeline: wgpu::RenderPipeline,
+    /// The plot axes, in scene space.
+    pub scene: AxesLayer,
+    /// The orientation gizmo, in its corner of the canvas.
+    pub gizmo: AxesLayer,
+}
+
+/// Uniforms and instance data for one set of axis lines and labels.
+pub struct AxesLayer {
+    /// Matrices for the lines, bound like an object's.
+    pub line_uniform_buffer: wgpu::Buffer,
+    pub line_bind_group: wgpu::BindGroup,
+    /// Matrices and viewport size for the labels, bound with the glyph atlas.
+    pub label_uniform_buffer: wgpu::Buffer,
+    pub label_bind_group: wgpu::BindGroup,
+    /// Rewritten every frame they are shown, and grown when they need more room.
+    pub line_buffer: RefCell<wgpu::Buffer>,
+    pub glyph_buffer: RefCell<wgpu::Buffer>,
+}
+
+/// Create the label pipeline, the glyph atlas, and the buffers the axes and
+/// the gizmo are drawn from.
+pub fn create_axes_resources(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    surface_format: wgpu::TextureFormat,
+    uniform_layout: &wgpu::BindGroupLayout,
+) -> AxesResources {
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Text Shader"),
+        source: wgpu::ShaderSource::Wgsl(include_str!("text.wgsl").into()),
+    });
+
+    let label_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("Label Bind Group Layout"),
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 2,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            },
+        ],
+    });
+
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Label Pipeline Layout"),
+        bind_group_layouts: &[&label_layout],
+        push_constant_ranges: &[],
+    });
+
+    // Labels go on top of everything, blended over it
+    let label_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Label Pipeline"),
+        layout: Some(&pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: &shader,
+            entry_point: Some("vs_glyphs"),
+            buffers: &[GlyphInstance::desc()],
+            compilation_options: Default::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader,
+            entry_point: Some("fs_glyphs"),
+            targets: &[Some(wgpu::ColorTargetState {
+                format: surface_format,
+                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: Default::default(),
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleStrip,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: None,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            unclipped_depth: false,
+            conservative: false,
+        },
+        depth_stencil: Some(wgpu::DepthStencilState {
+            format: wgpu::TextureFormat::Depth24Plus,
+            depth_write_enabled: false,
+            depth_compare: wgpu::CompareFunction::Always,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        }),
+        multisample: wgpu::MultisampleState {
+            count: MSAA_SAMPLE_COUNT,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+        multiview: None,
+        cache: None,
+    });
+
+    let atlas = device.create_texture_with_data(
+        queue,
+        &wgpu::TextureDescriptor {
+            label: Some("Glyph Atlas"),
+            size: wgpu::Extent3d {
+                width: glyphs::GLYPH_COUNT * glyphs::CELL_WIDTH,
+                height: glyphs::CELL_HEIGHT,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::R8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        },
+        wgpu::util::TextureDataOrder::LayerMajor,
+        &glyphs::atlas_pixels(),
+    );
+    let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+        label: Some("Glyph Sampler"),
+        mag_filter: wgpu::FilterMode::Linear,
+        min_filter: wgpu::FilterMode::Linear,
+        ..Default::default()
+    });
+
+    let atlas_view = atlas.create_view(&Default::default());
+    let layer = |name: &str| {
+        let label_uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(&format!("{} Label Uniform Buffer", name)),
+            // 80 bytes (MVP + viewport)
+            size: (16 + 4) * 4,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let label_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some(&format!("{} Label Bind Group", name)),
+            layout: &label_layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: label_uniform_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::TextureView(&atlas_view) },
+                wgpu::BindGroupEntry { binding: 2, resource: wgpu::BindingResource::Sampler(&sampler) },
+            ],
+        });
+        let (line_uniform_buffer, line_bind_group) = create_object_uniforms(device, uniform_layout);
+        AxesLayer {
+            line_uniform_buffer,
+            line_bind_group,
+            label_uniform_buffer,
+            label_bind_group,
+            line_buffer: RefCell::new(create_instance_buffer(device, &format!("{} Line Buffer", name), 1)),
+            glyph_buffer: RefCell::new(create_instance_buffer(device, &format!("{} Glyph Buffer", name), 1)),
+        }
+    };
+
+    AxesResources { label_pipeline, scene: layer("Axes"), gizmo: layer("Gizmo") }
+}
+
+/// Create a vertex buffer of `size` bytes for per-instance data rewritten each frame.
+fn create_instance_buffer(device: &wgpu::Device, label: &str, size: u64) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some(label),
+        size: size.next_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT),
+        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+/// Pipelines for weighted blended order-independent transparency.
+pub struct OitPipelines {
+    /// Accumulation pass for expanded and shared-vertex geometry.
+    pub accum: wgpu::RenderPipeline,
+    pub shared_accum: wgpu::RenderPipeline,
+    /// Fullscreen pass blending the resolved targets over the opaque scene.
+    pub composite: wgpu::RenderPipeline,
+    pub composite_layout: wgpu::BindGroupLayout,
+}
+
+/// Create the accumulation and composite pipelines for weighted blended transparency.
+pub fn create_oit_pipelines(
+    device: &wgpu::Device,
+    surface_format: wgpu::TextureFormat,
+    bind_group_layout: &wgpu::BindGroupLayout,
+    shared_mesh_layout: &wgpu::BindGroupLayout,
+) -> OitPipelines {
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Shader"),
+        source: wgpu::ShaderSource::Wgsl(include_str!("shader.wgsl").into()),
+    });
+    let composite_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("OIT Composite Shader"),
+        source: wgpu::ShaderSource::Wgsl(include_str!("oit_composite.wgsl").into()),
+    });
+
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Pipeline Layout"),
+        bind_group_layouts: &[bind_group_layout],
+        push_constant_ranges: &[],
+    });
+    let shared_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Shared Mesh Pipeline Layout"),
+        bind_group_layouts: &[bind_group_layout, shared_mesh_layout],
+        push_constant_ranges: &[],
+    });
+
+    // Color adds up; revealage is multiplied by each fragment's (1 - alpha)
+    let additive = wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::One,
+        dst_factor: wgpu::BlendFactor::One,
+        operation: wgpu::BlendOperation::Add,
+    };
+    let transmit = wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::Zero,
+        dst_factor: wgpu::BlendFactor::OneMinusSrc,
+        operation: wgpu::BlendOperation::Add,
+    };
+    let accum_targets = [
+        Some(wgpu::ColorTargetState {
+            format: OIT_ACCUM_FORMAT,
+            blend: Some(wgpu::BlendState { color: additive, alpha: additive }),
+            write_mask: wgpu::ColorWrites::ALL,
+        }),
+        Some(wgpu::ColorTargetState {
+            format: OIT_REVEALAGE_FORMAT,
+            blend: Some(wgpu::BlendState { color: transmit, alpha: transmit }),
+            write_mask: wgpu::ColorWrites::ALL,
+        }),
+    ];
+
+    let create_accum = |label: &str, layout: &wgpu::PipelineLayout, entry_point: &str, buffers: &[wgpu::VertexBufferLayout]| {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(label),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some(entry_point),
+                buffers,
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_oit_accum"),
+                targets: &accum_targets,
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: Some(wgpu::Face::Back),
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            // Tested against the opaque scene's depth, never written
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth24Plus,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: MSAA_SAMPLE_COUNT,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+            cache: None,
+        })
+    };
+    let accum = create_accum("OIT Accumulation Pipeline", &pipeline_layout, "vs_main", &[Vertex::desc()]);
+    let shared_accum = create_accum("Shared Mesh OIT Accumulation Pipeline", &shared_pipeline_layout, "vs_shared", &[]);
+
+    let texture_entry = |binding| wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::FRAGMENT,
+        ty: wgpu::BindingType::Texture {
+            sample_type: wgpu::TextureSampleType::Float { filterable: false },
+            view_dimension: wgpu::TextureViewDimension::D2,
+            multisampled: false,
+        },
+        count: None,
+    };
+    let composite_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("OIT Composite Bind Group Layout"),
+        entries: &[texture_entry(0), texture_entry(1)],
+    });
+    let composite_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("OIT Composite Pipeline Layout"),
+        bind_group_layouts: &[&composite_layout],
+        push_constant_ranges: &[],
+    });
+
+    let composite = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("OIT Composite Pipeline"),
+        layout: Some(&composite_pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: &composite_shader,
+            entry_point: Some("vs_fullscreen"),
+            buffers: &[],
+            compilation_options: Default::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &composite_shader,
+            entry_point: Some("fs_composite"),
+            targets: &[Some(wgpu::ColorTargetState {
+                format: surface_format,
+                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: Default::default(),
+        }),
+        primitive: wgpu::PrimitiveState::default(),
+        // The depth buffer stays attached for the selection outline drawn after
+        depth_stencil: Some(wgpu::DepthStencilState {
+            format: wgpu::TextureFormat::Depth24Plus,
+            depth_write_enabled: false,
+            depth_compare: wgpu::CompareFunction::Always,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        }),
+        multisample: wgpu::MultisampleState {
+            count: MSAA_SAMPLE_COUNT,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+        multiview: None,
+        cache: None,
+    });
+
+    OitPipelines {
+        accum,
+        shared_accum,
+        composite,
+        composite_layout,
+    }
+}
+
+/// Create the bind group layout for shared-vertex mesh storage buffers.
+pub fn create_shared_mesh_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+    let storage_entry = |binding| wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::VERTEX,
+        ty: wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Storage { read_only: true },
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    };
+
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("Shared Mesh Bind Group Layout"),
+        entries: &[storage_entry(0), storage_entry(1), storage_entry(2)],
+    })
+}
+
+/// Whether a shared-vertex mesh's storage buffers fit the device's binding limit.
+pub fn fits_shared_mesh(device: &wgpu::Device, mesh: &SharedMesh) -> bool {
+    let max = device.limits().max_storage_buffer_binding_size as usize;
+    std::mem::size_of_val(&*mesh.vertices) <= max && std::mem::size_of_val(&*mesh.indices) <= max
+}
+
+/// Upload a shared-vertex mesh and create the bind group `vs_shared` reads from.
+pub fn create_shared_mesh_geometry(
     device: &wgpu::Device,
-    uniform_buffer: &wgpu::Buffer,
-) -> (wgpu::BindGroupLayout, wgpu::BindGroup) {
-    let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+    layout: &wgpu::BindGroupLayout,
+    mesh: &SharedMesh,
+) -> ModelGeometry {
+    let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("Shared Vertex Buffer"),
+        contents: bytemuck::cast_slice(&mesh.vertices),
+        usage: wgpu::BufferUsages::STORAGE,
+    });
+    let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("Shared Index Buffer"),
+        contents: bytemuck::cast_slice(&mesh.indices),
+        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+    });
+    let face_id_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("Triangle Face Id Buffer"),
+        contents: bytemuck::cast_slice(&mesh.face_ids),
+        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+    });
+
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Shared Mesh Bind Group"),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry { binding: 0, resource: vertex_buffer.as_entire_binding() },
+            wgpu::BindGroupEntry { binding: 1, resource: index_buffer.as_entire_binding() },
+            wgpu::BindGroupEntry { binding: 2, resource: face_id_buffer.as_entire_binding() },
+        ],
+    });
+
+    ModelGeometry::Shared {
+        bind_group,
+        index_buffer,
+        face_id_buffer,
+        indices: mesh.indices.to_vec(),
+        face_ids: mesh.face_ids.to_vec(),
+    }
+}
+
+/// Rewrite a model's index data so triangle `order[i]` is drawn i-th.
+pub fn write_triangle_order(queue: &wgpu::Queue, geometry: &ModelGeometry, order: &[u32]) {
+    match geometry {
+        ModelGeometry::Expanded { index_buffer, indices, .. } => {
+            // Writes must be a multiple of 4 bytes; the buffer was padded to match on creation
+            let mut bytes = indices.reorder_triangles(order).as_bytes().to_vec();
+            bytes.resize(bytes.len().next_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT as usize), 0);
+            queue.write_buffer(index_buffer, 0, &bytes);
+        }
+        ModelGeometry::Shared { index_buffer, face_id_buffer, indices, face_ids, .. } => {
+            queue.write_buffer(index_buffer, 0, bytemuck::cast_slice(&reorder_triangles(indices, 3, order)));
+            queue.write_buffer(face_id_buffer, 0, bytemuck::cast_slice(&reorder_triangles(face_ids, 1, order)));
+        }
+        // Point clouds are never drawn transparent, and line-only objects have no triangles
+        ModelGeometry::Points { .. } | ModelGeometry::Empty => {}
+    }
+}
+
+/// Create the bind group layout for per-object uniforms.
+pub fn create_uniform_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
         label: Some("Bind Group Layout"),
         entries: &[wgpu::BindGroupLayoutEntry {
             binding: 0,
@@ -230,46 +1045,48 @@ pub fn create_bind_group(
             },
             count: None,
         }],
-    });
+    })
+}
 
+/// Create a uniform buffer and its bind group for one scene object.
+pub fn create_object_uniforms(device: &wgpu::Device, layout: &wgpu::BindGroupLayout) -> (wgpu::Buffer, wgpu::BindGroup) {
+    let uniform_buffer = create_uniform_buffer(device);
     let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
         label: Some("Bind Group"),
-        layout: &bind_group_layout,
+        layout,
         entries: &[wgpu::BindGroupEntry {
             binding: 0,
             resource: uniform_buffer.as_entire_binding(),
         }],
     });
 
-    (bind_group_layout, bind_group)
+    (uniform_buffer, bind_group)
 }
 
 /// Create the uniform buffer.
 pub fn create_uniform_buffer(device: &wgpu::Device) -> wgpu::Buffer {
     device.create_buffer(&wgpu::BufferDescriptor {
         label: Some("Uniform Buffer"),
-        size: (16 + 16 + 4 + 4 + 4) * 4, // 176 bytes (MVP + model + light_dir + camera_pos + selected_face)
+        // 240 bytes (MVP + model + light_dir + camera_pos + selected_face + point_params + point_right + point_up
+        // + line_params)
+        size: (16 + 16 + 4 + 4 + 4 + 4 + 4 + 4 + 4) * 4,
         usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
         mapped_at_creation: false,
     })
 }
 
-/// Create the wireframe vertex buffer.
-pub fn create_wireframe_buffer(device: &wgpu::Device) -> wgpu::Buffer {
+/// Create the wireframe vertex buffer with room for `vertex_count` line vertices.
+pub fn create_wireframe_buffer(device: &wgpu::Device, vertex_count: usize) -> wgpu::Buffer {
     device.create_buffer(&wgpu::BufferDescriptor {
         label: Some("Wireframe Buffer"),
-        size: (std::mem::size_of::<WireframeVertex>() * 6) as u64,
+        size: (std::mem::size_of::<WireframeVertex>() * vertex_count) as u64,
         usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
         mapped_at_creation: false,
     })
 }
 
-/// Create vertex and index buffers for a model.
-pub fn create_model_buffers(
-    device: &wgpu::Device,
-    vertices: &[Vertex],
-    indices: &[u16],
-) -> (wgpu::Buffer, wgpu::Buffer) {
+/// Upload per-face expanded geometry to vertex and index buffers.
+pub fn create_model_geometry(device: &wgpu::Device, vertices: &[Vertex], indices: &ModelIndices) -> ModelGeometry {
     let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
         label: Some("Vertex Buffer"),
         contents: bytemuck::cast_slice(vertices),
@@ -278,42 +1095,439 @@ pub fn create_model_buffers(
 
     let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
         label: Some("Index Buffer"),
-        contents: bytemuck::cast_slice(indices),
-        usage: wgpu::BufferUsages::INDEX,
+        contents: indices.as_bytes(),
+        usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST,
     });
 
-    (vertex_buffer, index_buffer)
+    ModelGeometry::Expanded {
+        vertex_buffer,
+        index_buffer,
+        index_format: indices.format(),
+        indices: indices.clone(),
+    }
 }
 
-/// Context holding all resources needed for the render loop.
-pub struct RenderContext {
+/// Upload a point cloud as one instance per point.
+pub fn create_point_geometry(device: &wgpu::Device, points: &[PointVertex]) -> ModelGeometry {
+    let instance_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("Point Buffer"),
+        contents: bytemuck::cast_slice(points),
+        usage: wgpu::BufferUsages::VERTEX,
+    });
+
+    ModelGeometry::Points { instance_buffer }
+}
+
+/// Upload polylines as one instance per segment, or `None` if there are none.
+pub fn create_line_geometry(device: &wgpu::Device, lines: &[Polyline]) -> Option<LineGeometry> {
+    let segments: Vec<LineSegment> = lines.iter().flat_map(Polyline::segments).collect();
+    if segments.is_empty() {
+        return None;
+    }
+    let instance_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("Line Buffer"),
+        contents: bytemuck::cast_slice(&segments),
+        usage: wgpu::BufferUsages::VERTEX,
+    });
+
+    Some(LineGeometry { instance_buffer, num_segments: segments.len() as u32 })
+}
+
+/// Pipelines and scratch buffers for drawing the scene into attachments of
+/// one color format, whether the canvas or an offscreen texture.
+pub struct SceneRenderer {
     pub device: Rc<wgpu::Device>,
     pub queue: Rc<wgpu::Queue>,
-    pub surface: Rc<RefCell<wgpu::Surface<'static>>>,
-    pub msaa_view: Rc<wgpu::TextureView>,
-    pub depth_view: Rc<wgpu::TextureView>,
-    pub render_pipeline: Rc<wgpu::RenderPipeline>,
-    pub transparent_pipeline: Rc<wgpu::RenderPipeline>,
-    pub wireframe_pipeline: Rc<wgpu::RenderPipeline>,
-    pub wireframe_buffer: Rc<wgpu::Buffer>,
-    pub bind_group: Rc<wgpu::BindGroup>,
-    pub uniform_buffer: Rc<wgpu::Buffer>,
+    /// Color format of the attachments the pipelines draw into.
+    pub format: wgpu::TextureFormat,
+    pub render_pipeline: wgpu::RenderPipeline,
+    pub transparent_pipeline: wgpu::RenderPipeline,
+    pub shared_pipeline: wgpu::RenderPipeline,
+    pub shared_transparent_pipeline: wgpu::RenderPipeline,
+    pub wireframe_pipeline: wgpu::RenderPipeline,
+    pub point_pipeline: wgpu::RenderPipeline,
+    pub line_pipeline: wgpu::RenderPipeline,
+    pub oit_pipelines: OitPipelines,
+    pub axes: AxesResources,
+    /// Grown in `draw` when the selected face's outline needs more room.
+    pub wireframe_buffer: RefCell<wgpu::Buffer>,
+}
+
+impl SceneRenderer {
+    pub fn new(
+        device: Rc<wgpu::Device>,
+        queue: Rc<wgpu::Queue>,
+        format: wgpu::TextureFormat,
+        uniform_layout: &wgpu::BindGroupLayout,
+        shared_mesh_layout: &wgpu::BindGroupLayout,
+    ) -> Self {
+        let (render_pipeline, transparent_pipeline, wireframe_pipeline) =
+            create_pipelines(&device, format, uniform_layout);
+        let (shared_pipeline, shared_transparent_pipeline) =
+            create_shared_pipelines(&device, format, uniform_layout, shared_mesh_layout);
+        let point_pipeline = create_point_pipeline(&device, format, uniform_layout);
+        let line_pipeline = create_line_pipeline(&device, format, uniform_layout);
+        let oit_pipelines = create_oit_pipelines(&device, format, uniform_layout, shared_mesh_layout);
+        let axes = create_axes_resources(&device, &queue, format, uniform_layout);
+        let wireframe_buffer = RefCell::new(create_wireframe_buffer(&device, 6));
+
+        SceneRenderer {
+            device,
+            queue,
+            format,
+            render_pipeline,
+            transparent_pipeline,
+            shared_pipeline,
+            shared_transparent_pipeline,
+            wireframe_pipeline,
+            point_pipeline,
+            line_pipeline,
+            oit_pipelines,
+            axes,
+            wireframe_buffer,
+        }
+    }
+
+    /// Write per-instance data to `buffer`, replacing it with a larger one if
+    /// it is too small.
+    fn write_instances(&self, buffer: &RefCell<wgpu::Buffer>, label: &str, bytes: &[u8]) {
+        if buffer.borrow().size() < bytes.len() as u64 {
+            *buffer.borrow_mut() = create_instance_buffer(&self.device, label, bytes.len() as u64);
+        }
+        self.queue.write_buffer(&buffer.borrow(), 0, bytes);
+    }
+
+    /// Record the scene as `state` views it into `target` over `background`,
+    /// resolved to `view`.
+    ///
+    /// Uniforms and depth-sorted index data are written through the queue, so
+    /// they land before the encoder's commands once it is submitted.
+    pub fn draw(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        state: &InteractionState,
+        model_res: &mut ModelResources,
+        target: &mut RenderTarget,
+        view: &wgpu::TextureView,
+        background: wgpu::Color,
+    ) {
+        // Create matrices
+        let model = state.model_matrix();
+        let camera_position = state.camera_position();
+        let view_matrix = mat4_look_at(camera_position, [0.0, 0.0, 0.0], [0.0, 1.0, 0.0]);
+        let aspect = target.width as f32 / target.height as f32;
+        let (near, far) = state.clip_planes();
+        let proj = state.projection_matrix(aspect, near, far);
+
+        // Light direction (world space, pointing from light to origin)
+        let light_dir = normalize(LIGHT_DIRECTION);
+
+        // Keep transparent objects' triangles back to front for the current view.
+        // Only the rotation affects the order; pan and zoom don't. Weighted
+        // blending doesn't depend on the order at all.
+        let weighted_blended = state.transparency == Transparency::WeightedBlended;
+        let view_rotation = mat4_from_quat(state.rotation);
+        let is_points = |resources: &ObjectResources| matches!(resources.geometry, ModelGeometry::Points { .. });
+        for resources in &mut model_res.objects {
+            let Some(object) = state
+                .object(resources.id)
+                .filter(|object| object.visible && object.transparent && !weighted_blended && !is_points(resources))
+            else {
+                continue;
+            };
+            let depth_sort = resources.depth_sort.get_or_insert_with(|| DepthSort::new(&object.triangles));
+            if let Some(order) = depth_sort.resort(&mat4_mul(object.transform, view_rotation)) {
+                write_triangle_order(&self.queue, &resources.geometry, &order);
+            }
+        }
+
+        let world_points = state.point_size_mode == PointSizeMode::World;
+        let point_params = [aspect, state.canvas_height as f32, state.point_size, if world_points { 1.0 } else { 0.0 }];
+        let line_join = match state.line_join {
+            LineJoin::Round => 0.0,
+            LineJoin::Miter => 1.0,
+            LineJoin::Bevel => 2.0,
+        };
+        let line_cap = match state.line_cap {
+            LineCap::Butt => 0.0,
+            LineCap::Round => 1.0,
+            LineCap::Square => 2.0,
+        };
+        let object_uniforms = |object_model: Mat4, selected_face: i32, flat_color: f32, line_params: [f32; 2]| {
+            let mvp = mat4_mul(mat4_mul(object_model, view_matrix), proj);
+            // The screen's right and up in object space, for splats sized in model
+            // units. The view only translates along Z, so these are the scene's X
+            // and Y axes mapped back through the object's inverse, whose columns
+            // are the normal matrix's rows up to scale.
+            let inverse = mat4_normal_matrix(&object_model);
+            let point_right = normalize([inverse[0][0], inverse[1][0], inverse[2][0]]);
+            let point_up = normalize([inverse[0][1], inverse[1][1], inverse[2][1]]);
+
+            let mut uniform_data = Vec::with_capacity(60);
+            uniform_data.extend_from_slice(&mat4_to_array(mvp));
+            uniform_data.extend_from_slice(&mat4_to_array(object_model));
+            uniform_data.extend_from_slice(&[light_dir[0], light_dir[1], light_dir[2], 0.0]);
+            uniform_data.extend_from_slice(&[camera_position[0], camera_position[1], camera_position[2], 0.0]);
+            uniform_data.extend_from_slice(&[selected_face as f32, flat_color, 0.0, 0.0]);
+            uniform_data.extend_from_slice(&point_params);
+            uniform_data.extend_from_slice(&[point_right[0], point_right[1], point_right[2], 0.0]);
+            uniform_data.extend_from_slice(&[point_up[0], point_up[1], point_up[2], 0.0]);
+            uniform_data.extend_from_slice(&[line_params[0], line_params[1], 0.0, 0.0]);
+            uniform_data
+        };
+
+        // Write each object's uniforms, its transform applied ahead of the view's
+        let mut draws = Vec::with_capacity(model_res.objects.len());
+        for resources in &model_res.objects {
+            let Some(object) = state.object(resources.id).filter(|object| object.visible) else {
+                continue;
+            };
+            let selected_face = if state.selected_object == Some(object.id) { state.selected_face } else { -1 };
+            let transparent = object.transparent && !is_points(resources);
+            let flat_color = if transparent { 1.0_f32 } else { 0.0 };
+            let uniform_data =
+                object_uniforms(mat4_mul(object.transform, model), selected_face, flat_color, [line_join, line_cap]);
+            self.queue.write_buffer(&resources.uniform_buffer, 0, bytemuck::cast_slice(&uniform_data));
+            draws.push((resources, transparent));
+        }
+
+        // Axes in scene space, like an object with no transform of its own,
+        // with round joins and butt caps whatever the polyline style
+        let axes_mvp = mat4_mul(mat4_mul(model, view_matrix), proj);
+        let (height, cells) = (state.canvas_height as f32, glyphs::GLYPH_COUNT as f32);
+        let (axes_segments, axes_glyphs) = match state.model_bounds.filter(|_| state.axes.visible()) {
+            Some(bounds) => axes::axes_geometry(&state.axes, &bounds, &axes_mvp, [height * aspect, height]),
+            None => (Vec::new(), Vec::new()),
+        };
+        if !axes_segments.is_empty() || !axes_glyphs.is_empty() {
+            let layer = &self.axes.scene;
+            let uniform_data = object_uniforms(model, -1, 0.0, [0.0, 0.0]);
+            self.queue.write_buffer(&layer.line_uniform_buffer, 0, bytemuck::cast_slice(&uniform_data));
+            let mut label_uniforms = mat4_to_array(axes_mvp).to_vec();
+            label_uniforms.extend_from_slice(&[height * aspect, height, cells, 0.0]);
+            self.queue.write_buffer(&layer.label_uniform_buffer, 0, bytemuck::cast_slice(&label_uniforms));
+            self.write_instances(&layer.line_buffer, "Axes Line Buffer", bytemuck::cast_slice(&axes_segments));
+            self.write_instances(&layer.glyph_buffer, "Axes Glyph Buffer", bytemuck::cast_slice(&axes_glyphs));
+        }
+        // Opaque objects first, so transparent ones blend over them
+        draws.sort_by_key(|&(_, transparent)| transparent);
+        // With weighted blending, transparent objects get passes of their own
+        let oit_draws = if weighted_blended { draws.iter().filter(|&&(_, transparent)| transparent).count() } else { 0 };
+        let main_draws = &draws[..draws.len() - oit_draws];
+
+        // Update wireframe buffer with the selected face's outline
+        let selected_bind_group = draws
+            .iter()
+            .find(|(resources, _)| Some(resources.id) == state.selected_object)
+            .map(|(resources, _)| &resources.bind_group);
+        let wireframe_vertex_count = if selected_bind_group.is_some() { state.selected_outline.len() as u32 } else { 0 };
+        if wireframe_vertex_count > 0 {
+            let wireframe_vertices: Vec<WireframeVertex> = state
+                .selected_outline
+                .iter()
+                .map(|&position| WireframeVertex { position })
+                .collect();
+            let size = std::mem::size_of_val(wireframe_vertices.as_slice()) as u64;
+            if self.wireframe_buffer.borrow().size() < size {
+                *self.wireframe_buffer.borrow_mut() = create_wireframe_buffer(&self.device, wireframe_vertices.len());
+            }
+            self.queue.write_buffer(&self.wireframe_buffer.borrow(), 0, bytemuck::cast_slice(&wireframe_vertices));
+        }
+        let wireframe_buffer = self.wireframe_buffer.borrow();
+        let glyph_buffer = self.axes.scene.glyph_buffer.borrow();
+        // The selected face's outline and the axis labels, on top of everything else
+        let draw_overlays = |render_pass: &mut wgpu::RenderPass| {
+            if let Some(bind_group) = selected_bind_group.filter(|_| wireframe_vertex_count > 0) {
+                render_pass.set_pipeline(&self.wireframe_pipeline);
+                render_pass.set_bind_group(0, Some(bind_group), &[]);
+                render_pass.set_vertex_buffer(0, wireframe_buffer.slice(..));
+                render_pass.draw(0..wireframe_vertex_count, 0..1);
+            }
+            if !axes_glyphs.is_empty() {
+                render_pass.set_pipeline(&self.axes.label_pipeline);
+                render_pass.set_bind_group(0, Some(&self.axes.scene.label_bind_group), &[]);
+                render_pass.set_vertex_buffer(0, glyph_buffer.slice(..));
+                render_pass.draw(0..4, 0..axes_glyphs.len() as u32);
+            }
+        };
+
+        if oit_draws > 0 && target.oit_targets.is_none() {
+            target.oit_targets = Some(create_oit_targets(
+                &self.device,
+                &self.oit_pipelines.composite_layout,
+                target.width,
+                target.height,
+            ));
+        }
+
+        // Render
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Render Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &target.msaa_view,
+                    resolve_target: Some(view),
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(background),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &target.depth_view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Store,
+                    }),
+                    stencil_ops: None,
+                }),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+
+            let opaque_draws = main_draws.iter().take_while(|&&(_, transparent)| !transparent).count();
+            for &(resources, _) in &main_draws[..opaque_draws] {
+                let (pipeline, shared_pipeline) = (&self.render_pipeline, &self.shared_pipeline);
+                draw_object(&mut render_pass, resources, pipeline, shared_pipeline, Some(&self.point_pipeline));
+            }
+            // Polylines are opaque whatever their object, so they go in before
+            // anything blends over them
+            for &(resources, _) in &draws {
+                draw_lines(&mut render_pass, resources, &self.line_pipeline);
+            }
+            if !axes_segments.is_empty() {
+                render_pass.set_pipeline(&self.line_pipeline);
+                render_pass.set_bind_group(0, Some(&self.axes.scene.line_bind_group), &[]);
+                render_pass.set_vertex_buffer(0, self.axes.scene.line_buffer.borrow().slice(..));
+                render_pass.draw(0..4, 0..axes_segments.len() as u32);
+            }
+            for &(resources, _) in &main_draws[opaque_draws..] {
+                let (pipeline, shared_pipeline) = (&self.transparent_pipeline, &self.shared_transparent_pipeline);
+                draw_object(&mut render_pass, resources, pipeline, shared_pipeline, None);
+            }
+
+            // Draw overlays, unless transparent layers still go on top
+            if oit_draws == 0 {
+                draw_overlays(&mut render_pass);
+            }
+        }
+
+        if let Some(oit_targets) = target.oit_targets.as_ref().filter(|_| oit_draws > 0) {
+            let transparent: Vec<&ObjectResources> = draws[draws.len() - oit_draws..].iter().map(|&(r, _)| r).collect();
+            let mut render_pass = draw_weighted_blended(
+                encoder,
+                &self.oit_pipelines,
+                oit_targets,
+                &target.msaa_view,
+                &target.depth_view,
+                view,
+                &transparent,
+            );
+            draw_overlays(&mut render_pass);
+        }
+    }
+
+    /// Record the orientation gizmo over the frame `draw` left in `target`,
+    /// in a square viewport of its own, resolved to `view` again.
+    ///
+    /// It has the depth buffer to itself, so the scene never hides it. Its
+    /// axes are colored like the plot axes, with round joins and caps.
+    pub fn draw_gizmo(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        state: &InteractionState,
+        target: &RenderTarget,
+        view: &wgpu::TextureView,
+    ) {
+        let (segments, labels) = gizmo::gizmo_geometry(&state.axes.style.axis_colors);
+        let layer = &self.axes.gizmo;
+        let mvp = gizmo::gizmo_mvp(state.rotation);
+        let size = gizmo::GIZMO_SIZE;
+
+        // Object uniforms as far as lines read them: the matrices, no
+        // selection, and the viewport size in CSS pixels for line widths
+        let mut uniform_data = Vec::with_capacity(60);
+        uniform_data.extend_from_slice(&mat4_to_array(mvp));
+        uniform_data.extend_from_slice(&mat4_to_array(mat4_from_quat(state.rotation)));
+        uniform_data.extend_from_slice(&[0.0; 8]);
+        uniform_data.extend_from_slice(&[-1.0, 0.0, 0.0, 0.0]);
+        uniform_data.extend_from_slice(&[1.0, size, 0.0, 0.0]);
+        uniform_data.extend_from_slice(&[0.0; 8]);
+        uniform_data.extend_from_slice(&[0.0, 1.0, 0.0, 0.0]);
+        self.queue.write_buffer(&layer.line_uniform_buffer, 0, bytemuck::cast_slice(&uniform_data));
+        let mut label_uniforms = mat4_to_array(mvp).to_vec();
+        label_uniforms.extend_from_slice(&[size, size, glyphs::GLYPH_COUNT as f32, 0.0]);
+        self.queue.write_buffer(&layer.label_uniform_buffer, 0, bytemuck::cast_slice(&label_uniforms));
+        self.write_instances(&layer.line_buffer, "Gizmo Line Buffer", bytemuck::cast_slice(&segments));
+        self.write_instances(&layer.glyph_buffer, "Gizmo Glyph Buffer", bytemuck::cast_slice(&labels));
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Gizmo Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &target.msaa_view,
+                resolve_target: Some(view),
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &target.depth_view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Discard,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        // Viewports are in physical pixels
+        let [x, y, width, height] = gizmo::viewport(state.canvas_width as f32, state.canvas_height as f32)
+            .map(|css| css * target.width as f32 / state.canvas_width.max(1) as f32);
+        render_pass.set_viewport(x, y, width, height, 0.0, 1.0);
+        render_pass.set_pipeline(&self.line_pipeline);
+        render_pass.set_bind_group(0, Some(&layer.line_bind_group), &[]);
+        render_pass.set_vertex_buffer(0, layer.line_buffer.borrow().slice(..));
+        render_pass.draw(0..4, 0..segments.len() as u32);
+        render_pass.set_pipeline(&self.axes.label_pipeline);
+        render_pass.set_bind_group(0, Some(&layer.label_bind_group), &[]);
+        render_pass.set_vertex_buffer(0, layer.glyph_buffer.borrow().slice(..));
+        render_pass.draw(0..4, 0..labels.len() as u32);
+    }
+}
+
+/// Context holding all resources needed for the render loop.
+pub struct RenderContext {
+    pub scene: Rc<SceneRenderer>,
+    pub surface: Rc<RefCell<SurfaceResources>>,
     pub model_resources: Rc<RefCell<ModelResources>>,
     pub state: Rc<RefCell<InteractionState>>,
-    pub aspect: f32,
 }
 
+/// The per-frame callback, shared so it can request the next frame with itself.
+type FrameCallback = Rc<RefCell<Option<Closure<dyn FnMut(f64)>>>>;
+
 /// Start the render loop.
 pub fn start_render_loop(
     ctx: RenderContext,
     window: web_sys::Window,
     debug_panel: web_sys::HtmlElement,
 ) {
-    let animation_callback: Rc<RefCell<Option<Closure<dyn FnMut()>>>> = Rc::new(RefCell::new(None));
+    let animation_callback: FrameCallback = Rc::new(RefCell::new(None));
     let animation_callback_clone = animation_callback.clone();
 
     let window_clone = window.clone();
-    *animation_callback_clone.borrow_mut() = Some(Closure::new(move || {
+    *animation_callback_clone.borrow_mut() = Some(Closure::new(move |time_ms: f64| {
+        // A ResizeObserver does not fire when only the device pixel ratio
+        // changes (e.g. the tab is dragged onto a HiDPI monitor), so poll it.
+        let device_pixel_ratio = window_clone.device_pixel_ratio();
+        let dpr_changed = ctx.surface.borrow().device_pixel_ratio != device_pixel_ratio;
+        if dpr_changed {
+            let canvas = ctx.surface.borrow().canvas.clone();
+            crate::resize(canvas.client_width() as u32, canvas.client_height() as u32, device_pixel_ratio);
+        }
+
+        ctx.state.borrow_mut().step_animation(time_ms);
         render_frame(&ctx, &debug_panel);
 
         window_clone
@@ -331,96 +1545,150 @@ fn render_frame(ctx: &RenderContext, debug_panel: &web_sys::HtmlElement) {
     let state = ctx.state.borrow();
 
     // Update debug panel
-    let rotation_x_deg = state.rotation_x.to_degrees();
-    let rotation_y_deg = state.rotation_y.to_degrees();
+    let (rotation_x, rotation_y) = crate::math::quat_to_euler_xy(state.rotation);
+    let rotation_x_deg = rotation_x.to_degrees();
+    let rotation_y_deg = rotation_y.to_degrees();
     let debug_text = format!(
         "Debug Panel\n\
          ───────────────────\n\
          Rotation X: {:.1}°\n\
          Rotation Y: {:.1}°\n\
          Zoom: {:.2}x\n\
-         Camera: (0, 0, 3)\n\n\
+         Camera: (0, 0, {:.3})\n\
+         Objects: {}\n\n\
          Controls\n\
          ───────────────────\n\
          /\tToggle debug panel\n\
          ⌘⇧P\tCommand palette\n\
          Drag\tRotate cube\n\
+         ⇧Drag\tPan\n\
          Scroll\tZoom in/out",
         rotation_x_deg,
         rotation_y_deg,
-        state.scale
+        state.scale,
+        state.camera_distance,
+        state.objects.len()
     );
     debug_panel.set_inner_text(&debug_text);
 
-    // Create matrices
-    let model = mat4_mul(
-        mat4_mul(mat4_scale(state.scale), mat4_rotate_x(state.rotation_x)),
-        mat4_rotate_y(state.rotation_y)
-    );
-    let view = mat4_look_at(CAMERA_POSITION, [0.0, 0.0, 0.0], [0.0, 1.0, 0.0]);
-    let proj = mat4_perspective(FIELD_OF_VIEW_DEG.to_radians(), ctx.aspect, NEAR_PLANE, FAR_PLANE);
-    let mvp = mat4_mul(mat4_mul(model, view), proj);
-
-    // Light direction (world space, pointing from light to origin)
-    let light_dir = normalize(LIGHT_DIRECTION);
-
-    // Write uniforms
-    let mut uniform_data = Vec::with_capacity(44);
-    uniform_data.extend_from_slice(&mat4_to_array(mvp));
-    uniform_data.extend_from_slice(&mat4_to_array(model));
-    uniform_data.extend_from_slice(&[light_dir[0], light_dir[1], light_dir[2], 0.0]);
-    uniform_data.extend_from_slice(&[CAMERA_POSITION[0], CAMERA_POSITION[1], CAMERA_POSITION[2], 0.0]);
-    let flat_color = if ctx.model_resources.borrow().transparent_mode { 1.0_f32 } else { 0.0 };
-    uniform_data.extend_from_slice(&[state.selected_face as f32, flat_color, 0.0, 0.0]);
-
-    ctx.queue.write_buffer(&ctx.uniform_buffer, 0, bytemuck::cast_slice(&uniform_data));
-
-    // Update wireframe buffer if a face is selected
-    let draw_wireframe = if state.selected_face >= 0 {
-        let face_idx = state.selected_face as usize;
-        if face_idx < state.model_triangles.len() {
-            let tri = &state.model_triangles[face_idx];
-            let wireframe_vertices = [
-                WireframeVertex { position: tri[0] },
-                WireframeVertex { position: tri[1] },
-                WireframeVertex { position: tri[1] },
-                WireframeVertex { position: tri[2] },
-                WireframeVertex { position: tri[2] },
-                WireframeVertex { position: tri[0] },
-            ];
-            ctx.queue.write_buffer(&ctx.wireframe_buffer, 0, bytemuck::cast_slice(&wireframe_vertices));
-            true
-        } else {
-            false
-        }
-    } else {
-        false
-    };
-
     // Render
-    let surface = ctx.surface.borrow();
-    let output = surface.get_current_texture().expect("Failed to get texture");
+    let mut surface = ctx.surface.borrow_mut();
+    let surface = &mut *surface;
+    let output = surface.surface.get_current_texture().expect("Failed to get texture");
     let view = output.texture.create_view(&wgpu::TextureViewDescriptor::default());
 
-    let mut encoder = ctx.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+    let mut encoder = ctx.scene.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
         label: Some("Render Encoder"),
     });
+    ctx.scene.draw(
+        &mut encoder,
+        &state,
+        &mut ctx.model_resources.borrow_mut(),
+        &mut surface.target,
+        &view,
+        BACKGROUND_COLOR,
+    );
+    if state.show_gizmo {
+        ctx.scene.draw_gizmo(&mut encoder, &state, &surface.target, &view);
+    }
+
+    ctx.scene.queue.submit(std::iter::once(encoder.finish()));
+    output.present();
+}
 
+/// Draw one object's geometry with the pipeline matching its kind.
+///
+/// Point clouds are only drawn in passes that supply `point_pipeline`; they
+/// are always opaque, so transparent passes leave it out.
+pub fn draw_object(
+    render_pass: &mut wgpu::RenderPass,
+    resources: &ObjectResources,
+    pipeline: &wgpu::RenderPipeline,
+    shared_pipeline: &wgpu::RenderPipeline,
+    point_pipeline: Option<&wgpu::RenderPipeline>,
+) {
+    match &resources.geometry {
+        ModelGeometry::Expanded { vertex_buffer, index_buffer, index_format, .. } => {
+            render_pass.set_pipeline(pipeline);
+            render_pass.set_bind_group(0, Some(&resources.bind_group), &[]);
+            render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+            render_pass.set_index_buffer(index_buffer.slice(..), *index_format);
+            render_pass.draw_indexed(0..resources.num_indices, 0, 0..1);
+        }
+        ModelGeometry::Shared { bind_group, .. } => {
+            render_pass.set_pipeline(shared_pipeline);
+            render_pass.set_bind_group(0, Some(&resources.bind_group), &[]);
+            render_pass.set_bind_group(1, Some(bind_group), &[]);
+            render_pass.draw(0..resources.num_indices, 0..1);
+        }
+        ModelGeometry::Points { instance_buffer } => {
+            let Some(point_pipeline) = point_pipeline else {
+                return;
+            };
+            render_pass.set_pipeline(point_pipeline);
+            render_pass.set_bind_group(0, Some(&resources.bind_group), &[]);
+            render_pass.set_vertex_buffer(0, instance_buffer.slice(..));
+            render_pass.draw(0..4, 0..resources.num_indices);
+        }
+        ModelGeometry::Empty => {}
+    }
+}
+
+/// Draw an object's polylines, if it has any.
+pub fn draw_lines(
+    render_pass: &mut wgpu::RenderPass,
+    resources: &ObjectResources,
+    line_pipeline: &wgpu::RenderPipeline,
+) {
+    if let Some(lines) = &resources.lines {
+        render_pass.set_pipeline(line_pipeline);
+        render_pass.set_bind_group(0, Some(&resources.bind_group), &[]);
+        render_pass.set_vertex_buffer(0, lines.instance_buffer.slice(..));
+        render_pass.draw(0..4, 0..lines.num_segments);
+    }
+}
+
+/// Draw transparent objects with weighted blended transparency over the
+/// opaque scene already in `msaa_view` and `depth_view`, then resolve to `view`.
+///
+/// The accumulation pass tests against the opaque depth buffer; the
+/// composite pass blends the result over the scene and is returned still
+/// open, for overlays such as the selection outline. Takes plain texture
+/// views, so it renders offscreen as well as to the canvas.
+pub fn draw_weighted_blended<'a>(
+    encoder: &'a mut wgpu::CommandEncoder,
+    pipelines: &OitPipelines,
+    targets: &OitTargets,
+    msaa_view: &wgpu::TextureView,
+    depth_view: &wgpu::TextureView,
+    view: &wgpu::TextureView,
+    draws: &[&ObjectResources],
+) -> wgpu::RenderPass<'a> {
     {
         let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-            label: Some("Render Pass"),
-            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                view: &ctx.msaa_view,
-                resolve_target: Some(&view),
-                ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(BACKGROUND_COLOR),
-                    store: wgpu::StoreOp::Store,
-                },
-            })],
+            label: Some("OIT Accumulation Pass"),
+            color_attachments: &[
+                Some(wgpu::RenderPassColorAttachment {
+                    view: &targets.accum_msaa_view,
+                    resolve_target: Some(&targets.accum_view),
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Discard,
+                    },
+                }),
+                Some(wgpu::RenderPassColorAttachment {
+                    view: &targets.revealage_msaa_view,
+                    resolve_target: Some(&targets.revealage_view),
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::WHITE),
+                        store: wgpu::StoreOp::Discard,
+                    },
+                }),
+            ],
             depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-                view: &ctx.depth_view,
+                view: depth_view,
                 depth_ops: Some(wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(1.0),
+                    load: wgpu::LoadOp::Load,
                     store: wgpu::StoreOp::Store,
                 }),
                 stencil_ops: None,
@@ -429,27 +1697,34 @@ fn render_frame(ctx: &RenderContext, debug_panel: &web_sys::HtmlElement) {
             occlusion_query_set: None,
         });
 
-        let model_res = ctx.model_resources.borrow();
-        let pipeline = if model_res.transparent_mode {
-            &ctx.transparent_pipeline
-        } else {
-            &ctx.render_pipeline
-        };
-        render_pass.set_pipeline(pipeline);
-        render_pass.set_bind_group(0, Some(&*ctx.bind_group), &[]);
-        render_pass.set_vertex_buffer(0, model_res.vertex_buffer.slice(..));
-        render_pass.set_index_buffer(model_res.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
-        render_pass.draw_indexed(0..model_res.num_indices, 0, 0..1);
-
-        // Draw wireframe around selected face
-        if draw_wireframe {
-            render_pass.set_pipeline(&ctx.wireframe_pipeline);
-            render_pass.set_bind_group(0, Some(&*ctx.bind_group), &[]);
-            render_pass.set_vertex_buffer(0, ctx.wireframe_buffer.slice(..));
-            render_pass.draw(0..6, 0..1);
+        for resources in draws {
+            draw_object(&mut render_pass, resources, &pipelines.accum, &pipelines.shared_accum, None);
         }
     }
 
-    ctx.queue.submit(std::iter::once(encoder.finish()));
-    output.present();
+    let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some("OIT Composite Pass"),
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view: msaa_view,
+            resolve_target: Some(view),
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Load,
+                store: wgpu::StoreOp::Store,
+            },
+        })],
+        depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+            view: depth_view,
+            depth_ops: Some(wgpu::Operations {
+                load: wgpu::LoadOp::Load,
+                store: wgpu::StoreOp::Store,
+            }),
+            stencil_ops: None,
+        }),
+        timestamp_writes: None,
+        occlusion_query_set: None,
+    });
+    render_pass.set_pipeline(&pipelines.composite);
+    render_pass.set_bind_group(0, Some(&targets.bind_group), &[]);
+    render_pass.draw(0..3, 0..1);
+    render_pass
 }
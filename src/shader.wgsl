@@ -3,6 +3,10 @@ const AMBIENT_STRENGTH: f32 = 0.15;
 const SPECULAR_STRENGTH: f32 = 0.5;
 const SPECULAR_SHININESS: f32 = 32.0;
 
+// Longest a mitered line join may reach past the joint, in half line widths,
+// before it is beveled instead
+const MITER_LIMIT: f32 = 4.0;
+
 // Selection highlight constants
 const HIGHLIGHT_BRIGHTNESS: f32 = 1.3;
 const HIGHLIGHT_BLUE_TINT: vec3<f32> = vec3<f32>(0.1, 0.1, 0.3);
@@ -14,6 +18,15 @@ struct Uniforms {
     camera_pos: vec4<f32>,
     // x: selected face id (-1 = none), y: flat_color flag (1.0 = skip lighting)
     selected_face: vec4<f32>,
+    // x: viewport aspect ratio, y: canvas height in CSS pixels, z: point size,
+    // w: 1.0 if the point size is in model units rather than pixels
+    point_params: vec4<f32>,
+    // Object-space directions that map to the screen's right and up, for
+    // splats sized in model units
+    point_right: vec4<f32>,
+    point_up: vec4<f32>,
+    // x: line join (0 round, 1 miter, 2 bevel), y: line cap (0 butt, 1 round, 2 square)
+    line_params: vec4<f32>,
 }
 
 @group(0) @binding(0)
@@ -36,10 +49,14 @@ struct VertexOutput {
 
 @vertex
 fn vs_main(in: VertexInput) -> VertexOutput {
+    return transform_vertex(in.position, in.normal, in.color, in.face_id);
+}
+
+fn transform_vertex(position: vec3<f32>, normal: vec3<f32>, color: vec4<f32>, face_id: u32) -> VertexOutput {
     var out: VertexOutput;
 
     // Transform position
-    out.clip_position = uniforms.mvp * vec4<f32>(in.position, 1.0);
+    out.clip_position = uniforms.mvp * vec4<f32>(position, 1.0);
 
     // Transform normal to world space (using upper-left 3x3 of model matrix)
     let normal_matrix = mat3x3<f32>(
@@ -47,19 +64,77 @@ fn vs_main(in: VertexInput) -> VertexOutput {
         uniforms.model[1].xyz,
         uniforms.model[2].xyz
     );
-    out.world_normal = normalize(normal_matrix * in.normal);
+    out.world_normal = normalize(normal_matrix * normal);
 
     // Transform position to world space
-    out.world_position = (uniforms.model * vec4<f32>(in.position, 1.0)).xyz;
+    out.world_position = (uniforms.model * vec4<f32>(position, 1.0)).xyz;
 
-    out.color = in.color;
-    out.face_id = in.face_id;
+    out.color = color;
+    out.face_id = face_id;
 
     return out;
 }
 
+// Shared-vertex meshes: vertices are not duplicated per face, so they are
+// pulled from storage buffers through the index list, and the face id comes
+// from a per-triangle table instead of the vertex. Drawn non-indexed with one
+// invocation per index. Scalar fields keep the layout free of vec3 padding.
+struct SharedVertex {
+    px: f32, py: f32, pz: f32,
+    nx: f32, ny: f32, nz: f32,
+    r: f32, g: f32, b: f32, a: f32,
+}
+
+@group(1) @binding(0)
+var<storage, read> shared_vertices: array<SharedVertex>;
+@group(1) @binding(1)
+var<storage, read> shared_indices: array<u32>;
+@group(1) @binding(2)
+var<storage, read> triangle_face_ids: array<u32>;
+
+@vertex
+fn vs_shared(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    let v = shared_vertices[shared_indices[vertex_index]];
+    return transform_vertex(
+        vec3<f32>(v.px, v.py, v.pz),
+        vec3<f32>(v.nx, v.ny, v.nz),
+        vec4<f32>(v.r, v.g, v.b, v.a),
+        triangle_face_ids[vertex_index / 3u],
+    );
+}
+
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return shade(in);
+}
+
+// Weighted blended order-independent transparency (McGuire & Bavoil 2013):
+// fragments add their premultiplied color, weighted towards near and opaque
+// ones, into one target and multiply their transmittance into another, so no
+// sorting is needed. `oit_composite.wgsl` resolves the two over the scene.
+struct OitOutput {
+    @location(0) accum: vec4<f32>,
+    @location(1) revealage: f32,
+}
+
+@fragment
+fn fs_oit_accum(in: VertexOutput) -> OitOutput {
+    let color = shade(in);
+    let depth = in.clip_position.z;
+    let weight = clamp(
+        pow(min(1.0, color.a * 10.0) + 0.01, 3.0) * 1e8 * pow(1.0 - depth * 0.9, 3.0),
+        1e-2,
+        3e3,
+    );
+
+    var out: OitOutput;
+    out.accum = vec4<f32>(color.rgb * color.a, color.a) * weight;
+    out.revealage = color.a;
+    return out;
+}
+
+// Lit (or flat) color of a fragment, with the selection highlight applied.
+fn shade(in: VertexOutput) -> vec4<f32> {
     let selected = i32(uniforms.selected_face.x);
 
     // Flat-color mode: skip lighting (used for voxel/transparent models so
@@ -100,6 +175,247 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     return vec4<f32>(result, in.color.a);
 }
 
+// Point clouds: each point is an instance expanded into a camera-facing quad
+// (a 4-vertex triangle strip) and cut to a disc, shaded like a small sphere.
+struct PointInput {
+    @location(0) position: vec3<f32>,
+    @location(1) size: f32,
+    @location(2) color: vec4<f32>,
+}
+
+struct PointOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    // Position within the splat, -1 to 1 on each axis
+    @location(0) corner: vec2<f32>,
+    @location(1) world_position: vec3<f32>,
+    @location(2) color: vec4<f32>,
+    @location(3) @interpolate(flat) index: u32,
+}
+
+@vertex
+fn vs_points(
+    @builtin(vertex_index) vertex_index: u32,
+    @builtin(instance_index) instance_index: u32,
+    in: PointInput,
+) -> PointOutput {
+    let corner = vec2<f32>(f32(vertex_index & 1u), f32(vertex_index >> 1u)) * 2.0 - 1.0;
+    let diameter = in.size * uniforms.point_params.z;
+
+    var out: PointOutput;
+    if (uniforms.point_params.w > 0.5) {
+        let offset = (corner.x * uniforms.point_right.xyz + corner.y * uniforms.point_up.xyz) * diameter * 0.5;
+        out.clip_position = uniforms.mvp * vec4<f32>(in.position + offset, 1.0);
+    } else {
+        // Offset in clip space, scaled by w so it stays `diameter` pixels after the divide
+        let center = uniforms.mvp * vec4<f32>(in.position, 1.0);
+        let offset = corner * diameter / uniforms.point_params.y;
+        out.clip_position = center + vec4<f32>(offset.x / uniforms.point_params.x, offset.y, 0.0, 0.0) * center.w;
+    }
+    out.corner = corner;
+    out.world_position = (uniforms.model * vec4<f32>(in.position, 1.0)).xyz;
+    out.color = in.color;
+    out.index = instance_index;
+    return out;
+}
+
+@fragment
+fn fs_points(in: PointOutput) -> @location(0) vec4<f32> {
+    let radius = length(in.corner);
+    // Fade out over the last pixel; with alpha to coverage this antialiases the rim
+    let edge = clamp((1.0 - radius) / max(fwidth(radius), 1e-4), 0.0, 1.0);
+    if (radius > 1.0) {
+        discard;
+    }
+
+    // The camera looks down -Z, so the screen's axes are the world's
+    var surface: VertexOutput;
+    surface.clip_position = in.clip_position;
+    surface.world_normal = vec3<f32>(in.corner, sqrt(1.0 - radius * radius));
+    surface.world_position = in.world_position;
+    surface.color = in.color;
+    surface.face_id = in.index;
+    let color = shade(surface);
+    return vec4<f32>(color.rgb, color.a * edge);
+}
+
+// Polylines: each segment is an instance expanded into a screen-space quad
+// around it, wide enough for its caps and joins. The fragment shader cuts the
+// line's outline out of the quad with a signed distance in CSS pixels, which
+// also gives the antialiased edge.
+struct LineInput {
+    @location(0) start: vec3<f32>,
+    @location(1) width: f32,
+    @location(2) end: vec3<f32>,
+    @location(3) line_id: u32,
+    @location(4) prev: vec4<f32>,
+    @location(5) next: vec4<f32>,
+    @location(6) start_color: vec4<f32>,
+    @location(7) end_color: vec4<f32>,
+}
+
+struct LineOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    // Position on screen in CSS pixels, from the viewport center
+    @location(0) @interpolate(linear) screen: vec2<f32>,
+    // Segment start and end on screen
+    @location(1) @interpolate(flat) segment: vec4<f32>,
+    // Neighboring points on screen, with z set for a join rather than a cap
+    @location(2) @interpolate(flat) prev: vec4<f32>,
+    @location(3) @interpolate(flat) next: vec4<f32>,
+    @location(4) @interpolate(flat) start_color: vec4<f32>,
+    @location(5) @interpolate(flat) end_color: vec4<f32>,
+    @location(6) @interpolate(flat) width: f32,
+    @location(7) @interpolate(flat) line_id: u32,
+}
+
+fn viewport_size() -> vec2<f32> {
+    return vec2<f32>(uniforms.point_params.y * uniforms.point_params.x, uniforms.point_params.y);
+}
+
+fn clip_to_screen(clip: vec4<f32>) -> vec2<f32> {
+    return clip.xy / clip.w * viewport_size() * 0.5;
+}
+
+// Moves `p` along the segment to `toward` up to the near plane (z = 0 in
+// WebGPU's depth range) if it is behind it, so w stays positive for the
+// perspective divide. Both points behind leaves `p` as it is.
+fn clip_to_near(p: vec4<f32>, toward: vec4<f32>) -> vec4<f32> {
+    if (p.z >= 0.0 || toward.z <= 0.0) {
+        return p;
+    }
+    return mix(p, toward, p.z / (p.z - toward.z));
+}
+
+@vertex
+fn vs_lines(@builtin(vertex_index) vertex_index: u32, in: LineInput) -> LineOutput {
+    var out: LineOutput;
+    let start = uniforms.mvp * vec4<f32>(in.start, 1.0);
+    let end = uniforms.mvp * vec4<f32>(in.end, 1.0);
+    if (start.z < 0.0 && end.z < 0.0) {
+        // Entirely behind the near plane: a degenerate quad draws nothing
+        out.clip_position = vec4<f32>(0.0, 0.0, -1.0, 1.0);
+        return out;
+    }
+    let clip_start = clip_to_near(start, end);
+    let clip_end = clip_to_near(end, start);
+    let prev = clip_to_near(uniforms.mvp * vec4<f32>(in.prev.xyz, 1.0), start);
+    let next = clip_to_near(uniforms.mvp * vec4<f32>(in.next.xyz, 1.0), end);
+    let a = clip_to_screen(clip_start);
+    let b = clip_to_screen(clip_end);
+
+    var dir = vec2<f32>(1.0, 0.0);
+    if (distance(a, b) > 1e-6) {
+        dir = normalize(b - a);
+    }
+    let normal = vec2<f32>(-dir.y, dir.x);
+
+    // Corners of a 4-vertex triangle strip: bit 0 picks the end, bit 1 the side.
+    // The extra pixel leaves room for the antialiased edge.
+    let at_end = (vertex_index & 1u) == 1u;
+    let side = f32(vertex_index >> 1u) * 2.0 - 1.0;
+    let half_width = in.width * 0.5;
+    let reach = half_width * MITER_LIMIT + 1.0;
+    var base = clip_start;
+    var offset = normal * side * (half_width + 1.0) - dir * reach;
+    if (at_end) {
+        base = clip_end;
+        offset = normal * side * (half_width + 1.0) + dir * reach;
+    }
+
+    out.clip_position = base + vec4<f32>(offset / (viewport_size() * 0.5) * base.w, 0.0, 0.0);
+    out.screen = clip_to_screen(base) + offset;
+    out.segment = vec4<f32>(a, b);
+    // An end cut off by the near plane gets neither a cap nor a join
+    out.prev = vec4<f32>(clip_to_screen(prev), select(in.prev.w, -1.0, start.z < 0.0), 0.0);
+    out.next = vec4<f32>(clip_to_screen(next), select(in.next.w, -1.0, end.z < 0.0), 0.0);
+    out.start_color = in.start_color;
+    out.end_color = in.end_color;
+    out.width = in.width;
+    out.line_id = in.line_id;
+    return out;
+}
+
+// Signed distance from `q`, past segment end `p` in direction `dir`, to the
+// cap or join there. `neighbor` is the next point along the line, with z set
+// for a join and negative for an end cut off by the near plane. `strip` is the
+// distance to this segment's sides.
+fn line_end_distance(
+    q: vec2<f32>,
+    p: vec2<f32>,
+    dir: vec2<f32>,
+    neighbor: vec4<f32>,
+    half_width: f32,
+    strip: f32,
+) -> f32 {
+    let beyond = dot(q - p, dir);
+    if (neighbor.z < -0.5) {
+        return max(strip, beyond);
+    }
+    let join = neighbor.z > 0.5;
+    let style = select(uniforms.line_params.y, uniforms.line_params.x, join);
+    // Round caps and joins
+    if ((join && style < 0.5) || (!join && style > 0.5 && style < 1.5)) {
+        return distance(q, p) - half_width;
+    }
+    if (!join) {
+        // Butt caps stop at the end; square ones carry on for half the width
+        return max(strip, select(beyond, beyond - half_width, style > 1.5));
+    }
+
+    let to_neighbor = neighbor.xy - p;
+    if (length(to_neighbor) < 1e-6 || dot(normalize(to_neighbor), dir) > 0.9999) {
+        // Straight on: the next segment covers it
+        return max(strip, beyond);
+    }
+    let next_dir = normalize(to_neighbor);
+    let next_strip = abs(dot(q - p, vec2<f32>(-next_dir.y, next_dir.x))) - half_width;
+    // The two segments' strips overlap in a parallelogram reaching out to the
+    // miter tip along the outer bisector; bevels cut it off at the corners
+    let bisector = normalize(dir - next_dir);
+    let corner = abs(dot(bisector, vec2<f32>(-dir.y, dir.x)));
+    var reach = half_width / max(corner, 1e-4);
+    if (style > 1.5 || reach > half_width * MITER_LIMIT) {
+        reach = half_width * corner;
+    }
+    return max(max(strip, next_strip), dot(q - p, bisector) - reach);
+}
+
+@fragment
+fn fs_lines(in: LineOutput) -> @location(0) vec4<f32> {
+    // CSS pixels per device pixel, for an edge one device pixel wide
+    let pixel = max(fwidth(in.screen.x), 1e-4);
+
+    let a = in.segment.xy;
+    let b = in.segment.zw;
+    let length_px = distance(a, b);
+    var dir = vec2<f32>(1.0, 0.0);
+    if (length_px > 1e-6) {
+        dir = (b - a) / length_px;
+    }
+    let along = dot(in.screen - a, dir);
+    let half_width = in.width * 0.5;
+    let strip = abs(dot(in.screen - a, vec2<f32>(-dir.y, dir.x))) - half_width;
+
+    var dist = strip;
+    if (along < 0.0) {
+        dist = line_end_distance(in.screen, a, -dir, in.prev, half_width, strip);
+    } else if (along > length_px) {
+        dist = line_end_distance(in.screen, b, dir, in.next, half_width, strip);
+    }
+    let coverage = clamp(0.5 - dist / pixel, 0.0, 1.0);
+    if (coverage <= 0.0) {
+        discard;
+    }
+
+    var color = mix(in.start_color, in.end_color, clamp(along / max(length_px, 1e-6), 0.0, 1.0));
+    let selected = i32(uniforms.selected_face.x);
+    if (selected >= 0 && u32(selected) == in.line_id) {
+        color = vec4<f32>(color.rgb * HIGHLIGHT_BRIGHTNESS + HIGHLIGHT_BLUE_TINT, color.a);
+    }
+    // With alpha to coverage the partial coverage antialiases the edge
+    return vec4<f32>(color.rgb, color.a * coverage);
+}
+
 // Wireframe shader for selected face outline
 struct WireframeVertexInput {
     @location(0) position: vec3<f32>,
@@ -0,0 +1,160 @@
+//! Back-to-front ordering of transparent geometry.
+
+use std::collections::HashMap;
+
+use crate::math::{cross, dot, sub, Mat4};
+
+/// Largest piece drawn as one unit; a voxel cube has 12 triangles.
+const MAX_PIECE_TRIANGLES: usize = 12;
+/// How far a corner may sit in front of a piece's face plane, relative to
+/// the face's size, for the piece to still count as convex.
+const CONVEX_TOLERANCE: f32 = 1e-4;
+
+/// A transparent object's triangles, grouped into pieces that are drawn in
+/// order of their centers' view depth.
+///
+/// A piece is a short run of consecutive triangles that closes up into a
+/// convex solid, the way a voxel emitter writes one cube after another: with
+/// back faces culled its own triangles never overlap one another, so it can
+/// stay whole. Every other triangle is sorted on its own.
+pub struct DepthSort {
+    // Center of each piece in object space
+    centers: Vec<[f32; 3]>,
+    // Start of each piece's triangles in `triangles`, plus a final end offset
+    offsets: Vec<usize>,
+    triangles: Vec<u32>,
+    // Object-space direction towards the camera that the current order was sorted for
+    sorted_axis: Option<[f32; 3]>,
+}
+
+impl DepthSort {
+    pub fn new(triangles: &[[[f32; 3]; 3]]) -> Self {
+        let mut sort = DepthSort {
+            centers: Vec::new(),
+            offsets: vec![0],
+            triangles: Vec::with_capacity(triangles.len()),
+            sorted_axis: None,
+        };
+        let mut start = 0;
+        while start < triangles.len() {
+            let end = (start + MAX_PIECE_TRIANGLES).min(triangles.len());
+            let run: Vec<u32> = (start as u32..end as u32).collect();
+            let piece = match closed_prefix(triangles, &run) {
+                Some(len) if is_convex(triangles, &run[..len]) => &run[..len],
+                _ => &run[..1],
+            };
+            sort.push_piece(triangles, piece);
+            start += piece.len();
+        }
+        sort
+    }
+
+    fn push_piece(&mut self, triangles: &[[[f32; 3]; 3]], piece: &[u32]) {
+        let mut sum = [0.0; 3];
+        for corner in piece.iter().flat_map(|&t| &triangles[t as usize]) {
+            for axis in 0..3 {
+                sum[axis] += corner[axis];
+            }
+        }
+        let count = (piece.len() * 3) as f32;
+        self.centers.push(sum.map(|s| s / count));
+        self.triangles.extend_from_slice(piece);
+        self.offsets.push(self.triangles.len());
+    }
+
+    /// Triangle order with the farthest pieces first, if `object_rotation`
+    /// (object transform, then view rotation) looks along a different axis
+    /// from the last sort.
+    pub fn resort(&mut self, object_rotation: &Mat4) -> Option<Vec<u32>> {
+        // View-space z of a point, less translation; larger is nearer the camera
+        let axis = [object_rotation[0][2], object_rotation[1][2], object_rotation[2][2]];
+        if self.sorted_axis == Some(axis) {
+            return None;
+        }
+        self.sorted_axis = Some(axis);
+
+        let mut pieces: Vec<usize> = (0..self.centers.len()).collect();
+        pieces.sort_by(|&a, &b| dot(self.centers[a], axis).total_cmp(&dot(self.centers[b], axis)));
+        Some(
+            pieces
+                .iter()
+                .flat_map(|&p| &self.triangles[self.offsets[p]..self.offsets[p + 1]])
+                .copied()
+                .collect(),
+        )
+    }
+}
+
+/// Length of the shortest prefix of `run` whose triangles close up, every
+/// edge shared by exactly two of them.
+fn closed_prefix(triangles: &[[[f32; 3]; 3]], run: &[u32]) -> Option<usize> {
+    let key = |p: [f32; 3]| p.map(f32::to_bits);
+    let mut edges: HashMap<([u32; 3], [u32; 3]), u32> = HashMap::new();
+    for (len, &t) in run.iter().enumerate() {
+        let tri = triangles[t as usize];
+        for i in 0..3 {
+            let (a, b) = (key(tri[i]), key(tri[(i + 1) % 3]));
+            *edges.entry(if a < b { (a, b) } else { (b, a) }).or_insert(0) += 1;
+        }
+        if edges.values().all(|&count| count == 2) {
+            return Some(len + 1);
+        }
+    }
+    None
+}
+
+/// Whether every corner of `piece` lies on or behind each of its triangles'
+/// front sides.
+fn is_convex(triangles: &[[[f32; 3]; 3]], piece: &[u32]) -> bool {
+    piece.iter().all(|&t| {
+        let [a, b, c] = triangles[t as usize];
+        let normal = cross(sub(b, a), sub(c, a));
+        let tolerance = CONVEX_TOLERANCE * dot(normal, normal).sqrt();
+        piece
+            .iter()
+            .flat_map(|&other| triangles[other as usize])
+            .all(|p| dot(normal, sub(p, a)) <= tolerance)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The 12 outward-facing triangles of the unit cube at `origin`.
+    fn cube(origin: [f32; 3]) -> Vec<[[f32; 3]; 3]> {
+        let corner = |i: usize| [0, 1, 2].map(|axis| origin[axis] + ((i >> axis) & 1) as f32);
+        let quads = [[0, 2, 3, 1], [4, 5, 7, 6], [0, 1, 5, 4], [2, 6, 7, 3], [0, 4, 6, 2], [1, 3, 7, 5]];
+        quads
+            .iter()
+            .flat_map(|q| [[q[0], q[1], q[2]], [q[0], q[2], q[3]]])
+            .map(|tri| tri.map(corner))
+            .collect()
+    }
+
+    #[test]
+    fn adjacent_cubes_sort_as_two_pieces() {
+        let mut triangles = cube([0.0; 3]);
+        triangles.extend(cube([1.0, 0.0, 0.0]));
+        let sort = DepthSort::new(&triangles);
+        assert_eq!(sort.offsets, [0, 12, 24]);
+
+        // Looking down +X the second cube is nearer, so it is drawn last
+        let order = DepthSort::new(&triangles).resort(&[[0.0, 0.0, 1.0, 0.0], [0.0; 4], [0.0; 4], [0.0; 4]]).unwrap();
+        assert!(order[..12].iter().all(|&t| t < 12));
+    }
+
+    #[test]
+    fn open_or_concave_solids_sort_by_triangle() {
+        // A cube missing one triangle is open
+        let open = &cube([0.0; 3])[1..];
+        assert_eq!(DepthSort::new(open).centers.len(), 11);
+
+        // A closed but concave solid: a cube with one corner pushed inwards
+        let dented: Vec<_> = cube([0.0; 3])
+            .into_iter()
+            .map(|tri| tri.map(|p| if p == [1.0; 3] { [0.4; 3] } else { p }))
+            .collect();
+        assert_eq!(DepthSort::new(&dented).centers.len(), 12);
+    }
+}
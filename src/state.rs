@@ -3,12 +3,277 @@
 use std::cell::RefCell;
 use std::rc::Rc;
 
+use crate::math::{
+    arcball_vector, dot, mat4_from_quat, mat4_mul, mat4_orthographic, mat4_perspective, mat4_scale, mat4_translate,
+    quat_between, quat_conjugate, quat_from_axis_angle, quat_from_euler_xy, quat_mul, quat_normalize, quat_slerp, sub,
+    transform_vector, Mat4, Quat, MAT4_IDENTITY, QUAT_IDENTITY,
+};
+use crate::model::{compute_bounds, Bounds, ModelIndices, Polyline};
+use crate::picking::{face_outline, FIELD_OF_VIEW_DEG};
+use crate::renderer::SceneRenderer;
+use crate::sorting::DepthSort;
+
 // Interaction constants
 pub const DEFAULT_ROTATION_X: f32 = -0.5;
 pub const DEFAULT_ROTATION_Y: f32 = 0.7;
 pub const DEFAULT_SCALE: f32 = 1.0;
 pub const ZOOM_MIN: f32 = 0.1;
 pub const ZOOM_MAX: f32 = 5.0;
+pub const DEFAULT_CAMERA_DISTANCE: f32 = 3.0;
+/// Extra room around the bounding sphere when fitting a model to the view.
+pub const FIT_MARGIN: f32 = 1.1;
+// Clip planes used before any model bounds are known
+const DEFAULT_NEAR_PLANE: f32 = 0.1;
+const DEFAULT_FAR_PLANE: f32 = 100.0;
+/// Turntable rotation per CSS pixel dragged, in radians.
+pub const TURNTABLE_SENSITIVITY: f32 = 0.01;
+pub const DEFAULT_VIEW_TRANSITION_MS: f64 = 400.0;
+/// Default point cloud point diameter, in CSS pixels.
+pub const DEFAULT_POINT_SIZE: f32 = 4.0;
+
+/// How dragging rotates the model.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RotationMode {
+    /// Free rotation about any axis, following the point grabbed on a virtual sphere.
+    Arcball,
+    /// Horizontal drags spin about the model's up axis and vertical drags tilt
+    /// it towards the viewer, so the model never rolls.
+    Turntable,
+}
+
+/// Standard views, named for the side of the model facing the camera.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ViewPreset {
+    Front,
+    Back,
+    Left,
+    Right,
+    Top,
+    Bottom,
+    /// Front, right and top sides equally foreshortened.
+    Isometric,
+}
+
+impl ViewPreset {
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "front" => ViewPreset::Front,
+            "back" => ViewPreset::Back,
+            "left" => ViewPreset::Left,
+            "right" => ViewPreset::Right,
+            "top" => ViewPreset::Top,
+            "bottom" => ViewPreset::Bottom,
+            "isometric" => ViewPreset::Isometric,
+            _ => return None,
+        })
+    }
+
+    /// Model rotation that turns this side of the model (+Z is the front,
+    /// +Y the top) towards the camera.
+    pub fn rotation(self) -> Quat {
+        let about_x = |deg: f32| quat_from_axis_angle([1.0, 0.0, 0.0], deg.to_radians());
+        let about_y = |deg: f32| quat_from_axis_angle([0.0, 1.0, 0.0], deg.to_radians());
+        match self {
+            ViewPreset::Front => QUAT_IDENTITY,
+            ViewPreset::Back => about_y(180.0),
+            ViewPreset::Left => about_y(90.0),
+            ViewPreset::Right => about_y(-90.0),
+            ViewPreset::Top => about_x(90.0),
+            ViewPreset::Bottom => about_x(-90.0),
+            // Turn the front-right edge to the camera, then tip the top towards it by atan(1/sqrt(2))
+            ViewPreset::Isometric => quat_mul(about_x(35.264_39), about_y(-45.0)),
+        }
+    }
+}
+
+/// A camera transition in progress, advanced once per frame by `step_animation`.
+pub struct CameraAnimation {
+    pub from_rotation: Quat,
+    pub to_rotation: Quat,
+    pub from_scale: f32,
+    pub to_scale: f32,
+    pub from_target: [f32; 3],
+    pub to_target: [f32; 3],
+    /// Frame time of the first step; `None` until the animation has been drawn once.
+    pub start_ms: Option<f64>,
+    pub duration_ms: f64,
+}
+
+/// Initial model rotation, from `DEFAULT_ROTATION_X` and `DEFAULT_ROTATION_Y`.
+pub fn default_rotation() -> Quat {
+    quat_from_euler_xy(DEFAULT_ROTATION_X, DEFAULT_ROTATION_Y)
+}
+
+/// How the view is projected onto the screen.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Projection {
+    /// Perspective with `FIELD_OF_VIEW_DEG`; zoom scales the model.
+    Perspective,
+    /// Parallel projection with no foreshortening; zoom shrinks the visible extent.
+    Orthographic,
+}
+
+impl Projection {
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "perspective" => Projection::Perspective,
+            "orthographic" => Projection::Orthographic,
+            _ => return None,
+        })
+    }
+}
+
+/// How transparent objects are blended.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Transparency {
+    /// Alpha blending, with triangles re-sorted back to front as the view rotates.
+    Sorted,
+    /// Weighted blended order-independent transparency: no sorting, and
+    /// correct-looking through intersecting surfaces, at the cost of two extra
+    /// render passes and approximate ordering of overlapping layers.
+    WeightedBlended,
+}
+
+impl Transparency {
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "sorted" => Transparency::Sorted,
+            "weighted" => Transparency::WeightedBlended,
+            _ => return None,
+        })
+    }
+}
+
+/// What point cloud point sizes are measured in.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PointSizeMode {
+    /// CSS pixels: points keep their size on screen as the view zooms.
+    Screen,
+    /// Model units: points grow and shrink with the model, like small spheres.
+    World,
+}
+
+impl PointSizeMode {
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "screen" => PointSizeMode::Screen,
+            "world" => PointSizeMode::World,
+            _ => return None,
+        })
+    }
+}
+
+/// How polylines are drawn where two segments meet.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LineJoin {
+    Round,
+    /// Sharp corners, beveled once the miter is longer than `MITER_LIMIT`
+    /// (in the shader) times the half width.
+    Miter,
+    Bevel,
+}
+
+impl LineJoin {
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "round" => LineJoin::Round,
+            "miter" => LineJoin::Miter,
+            "bevel" => LineJoin::Bevel,
+            _ => return None,
+        })
+    }
+}
+
+/// How the open ends of polylines are drawn.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LineCap {
+    /// Square, ending exactly at the end point.
+    Butt,
+    Round,
+    /// Square, extended half the line width past the end point.
+    Square,
+}
+
+impl LineCap {
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "butt" => LineCap::Butt,
+            "round" => LineCap::Round,
+            "square" => LineCap::Square,
+            _ => return None,
+        })
+    }
+}
+
+/// Which grid the axes draw behind the scene.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GridMode {
+    None,
+    /// The XZ plane under the scene.
+    Ground,
+    /// The three planes of the box around the scene that meet at its
+    /// lowest corner, where the axes start.
+    Box,
+}
+
+impl GridMode {
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "none" => GridMode::None,
+            "ground" => GridMode::Ground,
+            "box" => GridMode::Box,
+            _ => return None,
+        })
+    }
+}
+
+/// Colors and sizes of the axes, independent of the scene's models.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct AxesStyle {
+    /// Width of the axis lines and tick marks, in CSS pixels.
+    pub axis_width: f32,
+    pub grid_width: f32,
+    /// Height of label text, in CSS pixels.
+    pub label_size: f32,
+    /// Color of the X, Y and Z axes, their tick marks and their names.
+    pub axis_colors: [[f32; 4]; 3],
+    pub grid_color: [f32; 4],
+    /// Color of the tick values.
+    pub label_color: [f32; 4],
+}
+
+impl Default for AxesStyle {
+    fn default() -> Self {
+        AxesStyle {
+            axis_width: 2.0,
+            grid_width: 1.0,
+            label_size: 12.0,
+            axis_colors: [[0.9, 0.3, 0.3, 1.0], [0.4, 0.8, 0.4, 1.0], [0.4, 0.55, 0.95, 1.0]],
+            grid_color: [0.3, 0.3, 0.37, 1.0],
+            label_color: [0.85, 0.85, 0.9, 1.0],
+        }
+    }
+}
+
+/// What the axes subsystem draws around the scene's bounds; all off by default.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct AxesSettings {
+    /// X, Y and Z axis lines along the edges of the bounding box, and their names.
+    pub axes: bool,
+    pub grid: GridMode,
+    /// Tick marks at "nice" round values along each axis.
+    pub ticks: bool,
+    /// The tick values, next to the ticks.
+    pub labels: bool,
+    pub style: AxesStyle,
+}
+
+impl AxesSettings {
+    /// Whether anything is drawn at all.
+    pub fn visible(&self) -> bool {
+        self.axes || self.grid != GridMode::None || self.ticks || self.labels
+    }
+}
 
 /// Holds all interactive state for the 3D viewer.
 pub struct InteractionState {
@@ -16,24 +281,56 @@ pub struct InteractionState {
     pub is_dragging: bool,
     pub drag_start_x: f32,
     pub drag_start_y: f32,
-    pub initial_rotation_x: f32,
-    pub initial_rotation_y: f32,
+    pub initial_rotation: Quat,
+
+    // Pan drag state (right/middle/shift-drag, or two-finger drag)
+    pub is_panning: bool,
+    pub pan_start_x: f32,
+    pub pan_start_y: f32,
+    pub initial_camera_target: [f32; 3],
 
     // Transform state
-    pub rotation_x: f32,
-    pub rotation_y: f32,
+    pub rotation: Quat,
+    pub rotation_mode: RotationMode,
     pub scale: f32,
+    // Model-space point the camera looks at; rotation and zoom pivot about it
+    pub camera_target: [f32; 3],
+    pub projection: Projection,
+    pub transparency: Transparency,
+    // Diameter of point cloud points, scaled by each point's size factor
+    pub point_size: f32,
+    pub point_size_mode: PointSizeMode,
+    pub line_join: LineJoin,
+    pub line_cap: LineCap,
+    pub axes: AxesSettings,
+    // Orientation gizmo in the canvas's corner (see gizmo.rs)
+    pub show_gizmo: bool,
+    // Distance from the camera, on the +Z axis, to the camera target
+    pub camera_distance: f32,
 
     // Touch/pinch state
     pub is_pinching: bool,
     pub initial_pinch_distance: f32,
     pub initial_scale: f32,
 
-    // Face selection
+    // Selection: the picked object and a face id within it, so every
+    // triangle of a polygon highlights together, or a point index in a point
+    // cloud (-1 for none)
+    pub selected_object: Option<u32>,
     pub selected_face: i32,
+    // Line-list outline of the selected face in object space, rebuilt by `select`
+    pub selected_outline: Vec<[f32; 3]>,
 
-    // Model geometry for picking (triangles as 3 vertices each)
-    pub model_triangles: Vec<[[f32; 3]; 3]>,
+    // Scene objects, in draw order, and the id `add_object` hands out next
+    pub objects: Vec<SceneObject>,
+    pub next_object_id: u32,
+    // Bounds of every visible object in scene space, kept up to date by
+    // `update_scene_bounds`
+    pub model_bounds: Option<Bounds>,
+
+    // Camera transition in progress (view presets), and how long new ones take
+    pub camera_animation: Option<CameraAnimation>,
+    pub view_transition_ms: f64,
 
     // Canvas dimensions
     pub canvas_width: u32,
@@ -46,35 +343,463 @@ impl InteractionState {
             is_dragging: false,
             drag_start_x: 0.0,
             drag_start_y: 0.0,
-            initial_rotation_x: 0.0,
-            initial_rotation_y: 0.0,
-            rotation_x: DEFAULT_ROTATION_X,
-            rotation_y: DEFAULT_ROTATION_Y,
+            initial_rotation: default_rotation(),
+            is_panning: false,
+            pan_start_x: 0.0,
+            pan_start_y: 0.0,
+            initial_camera_target: [0.0; 3],
+            rotation: default_rotation(),
+            rotation_mode: RotationMode::Arcball,
             scale: DEFAULT_SCALE,
+            camera_target: [0.0; 3],
+            projection: Projection::Perspective,
+            transparency: Transparency::Sorted,
+            point_size: DEFAULT_POINT_SIZE,
+            point_size_mode: PointSizeMode::Screen,
+            line_join: LineJoin::Round,
+            line_cap: LineCap::Round,
+            axes: AxesSettings {
+                axes: false,
+                grid: GridMode::None,
+                ticks: false,
+                labels: false,
+                style: AxesStyle::default(),
+            },
+            show_gizmo: true,
+            camera_distance: DEFAULT_CAMERA_DISTANCE,
             is_pinching: false,
             initial_pinch_distance: 0.0,
             initial_scale: DEFAULT_SCALE,
+            selected_object: None,
             selected_face: -1,
-            model_triangles: Vec::new(),
+            selected_outline: Vec::new(),
+            objects: Vec::new(),
+            next_object_id: 0,
+            model_bounds: None,
+            camera_animation: None,
+            view_transition_ms: DEFAULT_VIEW_TRANSITION_MS,
             canvas_width,
             canvas_height,
         }
     }
+
+    /// Model matrix: move `camera_target` to the origin, rotate, then (in
+    /// perspective) scale.
+    pub fn model_matrix(&self) -> Mat4 {
+        let [tx, ty, tz] = self.camera_target;
+        let rotated = mat4_mul(mat4_translate([-tx, -ty, -tz]), mat4_from_quat(self.rotation));
+        match self.projection {
+            Projection::Perspective => mat4_mul(rotated, mat4_scale(self.scale)),
+            // Zoom changes the orthographic extent instead
+            Projection::Orthographic => rotated,
+        }
+    }
+
+    /// Half the visible height at the camera target's depth, in world units.
+    ///
+    /// Perspective and orthographic views agree on this at zoom 1, so
+    /// switching projections keeps the model the same size on screen.
+    pub fn visible_half_height(&self) -> f32 {
+        let half_height = self.camera_distance * (FIELD_OF_VIEW_DEG.to_radians() / 2.0).tan();
+        match self.projection {
+            Projection::Perspective => half_height,
+            Projection::Orthographic => half_height / self.scale,
+        }
+    }
+
+    /// Camera position in world space; it always looks down -Z at the origin.
+    pub fn camera_position(&self) -> [f32; 3] {
+        [0.0, 0.0, self.camera_distance]
+    }
+
+    /// Near and far clip planes that enclose the model's bounding sphere.
+    pub fn clip_planes(&self) -> (f32, f32) {
+        let Some(bounds) = self.model_bounds else {
+            return (DEFAULT_NEAR_PLANE, DEFAULT_FAR_PLANE);
+        };
+        // Farthest the model reaches from the camera target, in world units
+        let offset = sub(bounds.center, self.camera_target);
+        let mut extent = dot(offset, offset).sqrt() + bounds.radius;
+        if self.projection == Projection::Perspective {
+            extent *= self.scale;
+        }
+        let near = (self.camera_distance - extent).max(self.camera_distance * 0.001);
+        let far = self.camera_distance + extent;
+        (near * 0.99, far * 1.01)
+    }
+
+    /// Center the model's bounding sphere and move the camera back until it
+    /// fits the viewport, resetting zoom. Rotation is kept.
+    pub fn fit_to_view(&mut self) {
+        self.stop_camera_animation();
+        let Some(bounds) = self.model_bounds else {
+            return;
+        };
+        let aspect = self.canvas_width.max(1) as f32 / self.canvas_height.max(1) as f32;
+        let half_fov_y = FIELD_OF_VIEW_DEG.to_radians() / 2.0;
+        let half_fov_x = (half_fov_y.tan() * aspect).atan();
+        let radius = bounds.radius.max(1e-6);
+        self.camera_distance = FIT_MARGIN * radius / half_fov_y.min(half_fov_x).sin();
+        self.camera_target = bounds.center;
+        self.scale = DEFAULT_SCALE;
+    }
+
+    /// Projection matrix for a viewport with the given aspect ratio.
+    pub fn projection_matrix(&self, aspect: f32, near: f32, far: f32) -> Mat4 {
+        match self.projection {
+            Projection::Perspective => mat4_perspective(FIELD_OF_VIEW_DEG.to_radians(), aspect, near, far),
+            Projection::Orthographic => {
+                let half_height = self.visible_half_height();
+                mat4_orthographic(half_height * aspect, half_height, near, far)
+            }
+        }
+    }
+
+    /// Start a pan drag at canvas position (`x`, `y`) in CSS pixels.
+    pub fn begin_pan(&mut self, x: f32, y: f32) {
+        self.stop_camera_animation();
+        self.is_panning = true;
+        self.pan_start_x = x;
+        self.pan_start_y = y;
+        self.initial_camera_target = self.camera_target;
+    }
+
+    /// Update the camera target so the point under the cursor follows it.
+    pub fn drag_pan(&mut self, x: f32, y: f32) {
+        // Model units per CSS pixel at the depth of the camera target
+        let units_per_pixel = 2.0 * self.visible_half_height() / self.canvas_height.max(1) as f32;
+        let model_units_per_pixel = match self.projection {
+            Projection::Perspective => units_per_pixel / self.scale,
+            Projection::Orthographic => units_per_pixel,
+        };
+        let view_delta = [
+            (x - self.pan_start_x) * model_units_per_pixel,
+            (self.pan_start_y - y) * model_units_per_pixel,
+            0.0,
+        ];
+        // Moving the model right means moving the target left, in model space
+        let model_delta = transform_vector(view_delta, &mat4_from_quat(quat_conjugate(self.rotation)));
+        self.camera_target = [
+            self.initial_camera_target[0] - model_delta[0],
+            self.initial_camera_target[1] - model_delta[1],
+            self.initial_camera_target[2] - model_delta[2],
+        ];
+    }
+
+    /// Start a rotation drag at canvas position (`x`, `y`) in CSS pixels.
+    pub fn begin_rotation(&mut self, x: f32, y: f32) {
+        self.stop_camera_animation();
+        self.is_dragging = true;
+        self.drag_start_x = x;
+        self.drag_start_y = y;
+        self.initial_rotation = self.rotation;
+    }
+
+    /// Update the rotation for a drag that has reached (`x`, `y`).
+    pub fn drag_rotation(&mut self, x: f32, y: f32) {
+        let rotation = match self.rotation_mode {
+            RotationMode::Arcball => {
+                let (width, height) = (self.canvas_width as f32, self.canvas_height as f32);
+                let from = arcball_vector(self.drag_start_x, self.drag_start_y, width, height);
+                let to = arcball_vector(x, y, width, height);
+                quat_mul(quat_between(from, to), self.initial_rotation)
+            }
+            RotationMode::Turntable => {
+                // Yaw about the model's own up axis, then pitch about the screen's x axis
+                let yaw = quat_from_axis_angle([0.0, 1.0, 0.0], (x - self.drag_start_x) * TURNTABLE_SENSITIVITY);
+                let pitch = quat_from_axis_angle([1.0, 0.0, 0.0], (y - self.drag_start_y) * TURNTABLE_SENSITIVITY);
+                quat_mul(pitch, quat_mul(self.initial_rotation, yaw))
+            }
+        };
+        self.rotation = quat_normalize(rotation);
+    }
+
+    /// Move the camera to a new rotation, zoom and target over
+    /// `view_transition_ms`, or at once if that is zero. The zoom is clamped
+    /// to `ZOOM_MIN..ZOOM_MAX`, which also keeps its geometric easing finite.
+    pub fn animate_to(&mut self, rotation: Quat, scale: f32, target: [f32; 3]) {
+        let scale = scale.clamp(ZOOM_MIN, ZOOM_MAX);
+        if self.view_transition_ms <= 0.0 {
+            self.stop_camera_animation();
+            self.rotation = rotation;
+            self.scale = scale;
+            self.camera_target = target;
+            return;
+        }
+        self.camera_animation = Some(CameraAnimation {
+            from_rotation: self.rotation,
+            to_rotation: rotation,
+            from_scale: self.scale,
+            to_scale: scale,
+            from_target: self.camera_target,
+            to_target: target,
+            start_ms: None,
+            duration_ms: self.view_transition_ms,
+        });
+    }
+
+    /// Drop any camera transition in progress, so the camera stays where it
+    /// is, or where the caller is about to put it, instead of being pulled on
+    /// to the transition's end.
+    pub fn stop_camera_animation(&mut self) {
+        self.camera_animation = None;
+    }
+
+    /// Turn the camera to a standard view, keeping the zoom and pan.
+    pub fn turn_to(&mut self, preset: ViewPreset) {
+        self.animate_to(preset.rotation(), self.scale, self.camera_target);
+    }
+
+    /// Advance any camera transition to frame time `now_ms`.
+    pub fn step_animation(&mut self, now_ms: f64) {
+        let Some(animation) = self.camera_animation.as_mut() else {
+            return;
+        };
+        let start_ms = *animation.start_ms.get_or_insert(now_ms);
+        let t = ((now_ms - start_ms) / animation.duration_ms).clamp(0.0, 1.0) as f32;
+        // Ease in and out so the camera doesn't jerk at either end
+        let eased = t * t * (3.0 - 2.0 * t);
+        let lerp = |a: f32, b: f32| a + (b - a) * eased;
+
+        self.rotation = quat_slerp(animation.from_rotation, animation.to_rotation, eased);
+        // Interpolate zoom geometrically so equal times give equal zoom ratios
+        self.scale = animation.from_scale * (animation.to_scale / animation.from_scale).powf(eased);
+        self.camera_target = [0, 1, 2].map(|i| lerp(animation.from_target[i], animation.to_target[i]));
+        if t >= 1.0 {
+            self.scale = animation.to_scale;
+            self.camera_animation = None;
+        }
+    }
+
+    /// Select a face of an object as `(object id, face id)`, or nothing, and
+    /// rebuild its outline.
+    pub fn select(&mut self, hit: Option<(u32, u32)>) {
+        let object = hit.and_then(|(id, face)| Some((self.object(id)?, face)));
+        let outline = object.map_or_else(Vec::new, |(object, face)| face_outline(&object.triangles, &object.face_ids, face));
+        let hit = object.map(|(object, face)| (object.id, face));
+        self.selected_object = hit.map(|(id, _)| id);
+        self.selected_face = hit.map_or(-1, |(_, face)| face as i32);
+        self.selected_outline = outline;
+    }
+
+    pub fn object(&self, id: u32) -> Option<&SceneObject> {
+        self.objects.iter().find(|object| object.id == id)
+    }
+
+    pub fn object_mut(&mut self, id: u32) -> Option<&mut SceneObject> {
+        self.objects.iter_mut().find(|object| object.id == id)
+    }
+
+    /// Remove every object, dropping the selection.
+    pub fn clear_objects(&mut self) {
+        self.objects.clear();
+        self.next_object_id = 0;
+        self.select(None);
+    }
+
+    /// Add object `id`, or give it new picking triangles and polylines if it
+    /// already exists (keeping its transform, visibility and transparency),
+    /// dropping any selection on it.
+    pub fn set_object_geometry(
+        &mut self,
+        id: u32,
+        triangles: Vec<[[f32; 3]; 3]>,
+        face_ids: Vec<u32>,
+        lines: Vec<Polyline>,
+    ) {
+        let mut points = triangles.as_flattened().to_vec();
+        points.extend(lines.iter().flat_map(|line| &line.positions));
+        let bounds = compute_bounds(&points);
+        self.set_object_shape(id, bounds, |object| {
+            object.triangles = triangles;
+            object.face_ids = face_ids;
+            object.lines = lines;
+            object.points = Vec::new();
+            object.point_sizes = Vec::new();
+        });
+    }
+
+    /// Like `set_object_geometry`, for a point cloud picked by its points
+    /// and their size factors.
+    pub fn set_object_points(&mut self, id: u32, points: Vec<[f32; 3]>, point_sizes: Vec<f32>) {
+        let bounds = compute_bounds(&points);
+        self.set_object_shape(id, bounds, |object| {
+            object.triangles = Vec::new();
+            object.face_ids = Vec::new();
+            object.lines = Vec::new();
+            object.points = points;
+            object.point_sizes = point_sizes;
+        });
+    }
+
+    fn set_object_shape(&mut self, id: u32, bounds: Option<Bounds>, set: impl FnOnce(&mut SceneObject)) {
+        let index = match self.objects.iter().position(|object| object.id == id) {
+            Some(index) => index,
+            None => {
+                self.objects.push(SceneObject {
+                    id,
+                    transform: MAT4_IDENTITY,
+                    visible: true,
+                    transparent: false,
+                    triangles: Vec::new(),
+                    face_ids: Vec::new(),
+                    lines: Vec::new(),
+                    points: Vec::new(),
+                    point_sizes: Vec::new(),
+                    bounds,
+                });
+                self.objects.len() - 1
+            }
+        };
+        let object = &mut self.objects[index];
+        set(object);
+        object.bounds = bounds;
+        self.next_object_id = self.next_object_id.max(id + 1);
+        if self.selected_object == Some(id) {
+            self.select(None);
+        }
+        self.update_scene_bounds();
+    }
+
+    /// Recompute `model_bounds` from the visible objects' transformed bounds.
+    pub fn update_scene_bounds(&mut self) {
+        self.model_bounds = self
+            .objects
+            .iter()
+            .filter(|object| object.visible)
+            .filter_map(|object| Some(object.bounds?.transformed(&object.transform)))
+            .reduce(|a, b| a.union(&b));
+    }
+}
+
+/// One object in the scene: its placement, and its geometry for picking.
+///
+/// The matching GPU buffers are the `ObjectResources` with the same `id`.
+pub struct SceneObject {
+    pub id: u32,
+    /// Object-to-scene transform, applied before the view's pan, rotation and zoom.
+    pub transform: Mat4,
+    pub visible: bool,
+    /// Draw with the transparent pipeline (depth writes disabled) after the
+    /// opaque objects. Point clouds and polylines ignore it.
+    pub transparent: bool,
+    // Object-space triangles for picking (3 vertices each), with the face id of each
+    pub triangles: Vec<[[f32; 3]; 3]>,
+    pub face_ids: Vec<u32>,
+    // Object-space polylines, picked by their ids alongside the faces
+    pub lines: Vec<Polyline>,
+    // Object-space points of a point cloud, with each one's size factor
+    // (empty for all 1); picked by point index instead of face id
+    pub points: Vec<[f32; 3]>,
+    pub point_sizes: Vec<f32>,
+    // Object-space bounds, before `transform`
+    pub bounds: Option<Bounds>,
 }
 
 /// GPU resources needed for dynamic model loading.
 pub struct GpuResources {
     pub device: Rc<wgpu::Device>,
+    pub uniform_layout: Rc<wgpu::BindGroupLayout>,
+    pub shared_mesh_layout: Rc<wgpu::BindGroupLayout>,
 }
 
-/// Buffers for the currently loaded model.
-pub struct ModelResources {
-    pub vertex_buffer: wgpu::Buffer,
-    pub index_buffer: wgpu::Buffer,
+/// GPU geometry for one scene object.
+///
+/// The triangle variants keep a CPU copy of their triangle data so the index
+/// buffers can be rewritten in a new triangle order for depth sorting.
+pub enum ModelGeometry {
+    /// Per-face expanded vertices, drawn from vertex and index buffers.
+    Expanded {
+        vertex_buffer: wgpu::Buffer,
+        index_buffer: wgpu::Buffer,
+        /// Uint16 for models with at most 65,536 vertices, Uint32 otherwise.
+        index_format: wgpu::IndexFormat,
+        indices: ModelIndices,
+    },
+    /// Shared vertices, indices and per-triangle face ids in storage buffers,
+    /// pulled by the `vs_shared` shader entry point.
+    Shared {
+        bind_group: wgpu::BindGroup,
+        index_buffer: wgpu::Buffer,
+        face_id_buffer: wgpu::Buffer,
+        indices: Vec<u32>,
+        face_ids: Vec<u32>,
+    },
+    /// Point cloud, one `PointVertex` instance per point, drawn by `vs_points`.
+    Points { instance_buffer: wgpu::Buffer },
+    /// No triangles or points, for an object made only of polylines.
+    Empty,
+}
+
+/// Polyline segments, one `LineSegment` instance each, drawn by `vs_lines`.
+pub struct LineGeometry {
+    pub instance_buffer: wgpu::Buffer,
+    pub num_segments: u32,
+}
+
+/// Buffers and uniforms for one scene object.
+pub struct ObjectResources {
+    pub id: u32,
+    pub geometry: ModelGeometry,
+    /// Indices to draw, or points for a point cloud.
     pub num_indices: u32,
-    /// When true the transparent pipeline is used (depth writes disabled),
-    /// enabling alpha-blended geometry to show through itself correctly.
-    pub transparent_mode: bool,
+    /// The object's polylines, if it has any.
+    pub lines: Option<LineGeometry>,
+    /// This object's matrices and selection, written every frame.
+    pub uniform_buffer: wgpu::Buffer,
+    pub bind_group: wgpu::BindGroup,
+    /// Built the first time the object is drawn transparent.
+    pub depth_sort: Option<DepthSort>,
+}
+
+/// GPU buffers for every object in the scene.
+pub struct ModelResources {
+    pub objects: Vec<ObjectResources>,
+}
+
+impl ModelResources {
+    /// Add an object's resources, replacing any with the same id.
+    pub fn set_object(&mut self, resources: ObjectResources) {
+        match self.objects.iter_mut().find(|object| object.id == resources.id) {
+            Some(existing) => *existing = resources,
+            None => self.objects.push(resources),
+        }
+    }
+}
+
+/// The canvas surface and the size-dependent render attachments.
+///
+/// Rebuilt by `renderer::resize_surface` whenever the canvas size or the
+/// device pixel ratio changes.
+pub struct SurfaceResources {
+    pub canvas: web_sys::HtmlCanvasElement,
+    pub surface: wgpu::Surface<'static>,
+    pub config: wgpu::SurfaceConfiguration,
+    pub target: RenderTarget,
+    pub device_pixel_ratio: f64,
+}
+
+/// Size-dependent attachments a frame is drawn into before it is resolved
+/// to the canvas or an offscreen texture.
+pub struct RenderTarget {
+    pub width: u32,
+    pub height: u32,
+    pub msaa_view: wgpu::TextureView,
+    pub depth_view: wgpu::TextureView,
+    /// Created the first time weighted blended transparency is drawn.
+    pub oit_targets: Option<OitTargets>,
+}
+
+/// Render targets for weighted blended transparency: multisampled
+/// accumulation and revealage attachments, and the single-sample textures
+/// they resolve to for the composite pass.
+pub struct OitTargets {
+    pub accum_msaa_view: wgpu::TextureView,
+    pub accum_view: wgpu::TextureView,
+    pub revealage_msaa_view: wgpu::TextureView,
+    pub revealage_view: wgpu::TextureView,
+    /// Binds the resolved textures for `fs_composite`.
+    pub bind_group: wgpu::BindGroup,
 }
 
 // Thread-local storage for global state access from wasm_bindgen exports
@@ -82,4 +807,37 @@ thread_local! {
     pub static INTERACTION_STATE: RefCell<Option<Rc<RefCell<InteractionState>>>> = RefCell::new(None);
     pub static GPU_RESOURCES: RefCell<Option<GpuResources>> = RefCell::new(None);
     pub static MODEL_RESOURCES: RefCell<Option<Rc<RefCell<ModelResources>>>> = RefCell::new(None);
+    pub static SURFACE_RESOURCES: RefCell<Option<Rc<RefCell<SurfaceResources>>>> = const { RefCell::new(None) };
+    pub static SCENE_RENDERER: RefCell<Option<Rc<SceneRenderer>>> = const { RefCell::new(None) };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn direct_camera_changes_stop_a_transition() {
+        let mut state = InteractionState::new(100, 100);
+        state.turn_to(ViewPreset::Top);
+        assert!(state.camera_animation.is_some());
+        state.fit_to_view();
+        assert!(state.camera_animation.is_none());
+
+        state.turn_to(ViewPreset::Top);
+        state.begin_pan(0.0, 0.0);
+        state.step_animation(0.0);
+        state.step_animation(1e6);
+        assert_eq!(state.rotation, default_rotation());
+    }
+
+    #[test]
+    fn zoom_transition_to_zero_stays_finite() {
+        let mut state = InteractionState::new(100, 100);
+        state.animate_to(default_rotation(), 0.0, [0.0; 3]);
+        state.step_animation(0.0);
+        state.step_animation(state.view_transition_ms * 0.5);
+        assert!(state.scale.is_finite() && state.scale > 0.0, "scale is {}", state.scale);
+        state.step_animation(1e6);
+        assert_eq!(state.scale, ZOOM_MIN);
+    }
 }
@@ -0,0 +1,53 @@
+// Labels: one screen-aligned quad per character, placed around a projected
+// scene-space anchor and textured from the glyph atlas (see glyphs.rs).
+
+struct TextUniforms {
+    mvp: mat4x4<f32>,
+    // x, y: viewport size in CSS pixels, z: number of cells in the atlas
+    viewport: vec4<f32>,
+}
+
+@group(0) @binding(0)
+var<uniform> uniforms: TextUniforms;
+@group(0) @binding(1)
+var glyph_atlas: texture_2d<f32>;
+@group(0) @binding(2)
+var glyph_sampler: sampler;
+
+struct GlyphInput {
+    @location(0) anchor: vec3<f32>,
+    @location(1) glyph: u32,
+    @location(2) offset: vec2<f32>,
+    @location(3) size: vec2<f32>,
+    @location(4) color: vec4<f32>,
+}
+
+struct GlyphOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+@vertex
+fn vs_glyphs(@builtin(vertex_index) vertex_index: u32, in: GlyphInput) -> GlyphOutput {
+    // Corners of a 4-vertex triangle strip: bit 0 picks the right, bit 1 the top
+    let corner = vec2<f32>(f32(vertex_index & 1u), f32(vertex_index >> 1u));
+    let anchor = uniforms.mvp * vec4<f32>(in.anchor, 1.0);
+    let offset = (in.offset + corner * in.size) / (uniforms.viewport.xy * 0.5);
+
+    var out: GlyphOutput;
+    out.clip_position = anchor + vec4<f32>(offset * anchor.w, 0.0, 0.0);
+    // Atlas rows run top to bottom
+    out.uv = vec2<f32>((f32(in.glyph) + corner.x) / uniforms.viewport.z, 1.0 - corner.y);
+    out.color = in.color;
+    return out;
+}
+
+@fragment
+fn fs_glyphs(in: GlyphOutput) -> @location(0) vec4<f32> {
+    let coverage = textureSample(glyph_atlas, glyph_sampler, in.uv).r;
+    if (coverage <= 0.0) {
+        discard;
+    }
+    return vec4<f32>(in.color.rgb, in.color.a * coverage);
+}
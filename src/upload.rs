@@ -0,0 +1,92 @@
+//! Model upload: turning parsed geometry into an object's GPU buffers and
+//! picking geometry, for both the browser viewer and headless rendering.
+
+use crate::model::{extract_face_ids, extract_triangles, ModelIndices, Polyline, SharedMesh};
+use crate::renderer;
+use crate::state::{GpuResources, InteractionState, ModelGeometry, ModelResources, ObjectResources};
+use crate::vertex::{PointVertex, Vertex};
+
+/// Id of the object the `load_*` functions replace the scene with.
+pub const MAIN_OBJECT_ID: u32 = 0;
+
+/// The parts of a viewer a model upload touches.
+pub struct SceneUpload<'a> {
+    pub gpu: &'a GpuResources,
+    pub model: &'a mut ModelResources,
+    pub state: &'a mut InteractionState,
+}
+
+impl SceneUpload<'_> {
+    /// Clear the scene, add one object as `MAIN_OBJECT_ID` with `upload`, and frame it.
+    pub fn replace_scene(&mut self, upload: impl FnOnce(&mut Self, u32)) {
+        self.model.objects.clear();
+        self.state.clear_objects();
+        upload(self, MAIN_OBJECT_ID);
+        self.state.fit_to_view();
+    }
+
+    /// Give object `id` per-face expanded geometry, and any polylines.
+    pub fn upload_model(&mut self, id: u32, vertices: &[Vertex], indices: &ModelIndices, lines: &[Polyline]) {
+        let geometry = renderer::create_model_geometry(&self.gpu.device, vertices, indices);
+        let triangles = extract_triangles(vertices, indices);
+        let face_ids = extract_face_ids(vertices, indices);
+        self.set_object_geometry(id, geometry, indices.len() as u32, triangles, face_ids, lines);
+    }
+
+    /// Give object `id` a shared-vertex mesh and its polylines, falling back
+    /// to expanded geometry if it exceeds the device's storage buffer limits.
+    pub fn upload_shared_model(&mut self, id: u32, mesh: &SharedMesh) {
+        if !renderer::fits_shared_mesh(&self.gpu.device, mesh) {
+            let (vertices, indices) = mesh.expand();
+            self.upload_model(id, &vertices, &indices, &mesh.lines);
+            return;
+        }
+        let geometry = if mesh.indices.is_empty() {
+            // Only polylines: there is nothing for the triangle pipelines to draw
+            ModelGeometry::Empty
+        } else {
+            renderer::create_shared_mesh_geometry(&self.gpu.device, &self.gpu.shared_mesh_layout, mesh)
+        };
+        let num_indices = mesh.indices.len() as u32;
+        self.set_object_geometry(id, geometry, num_indices, mesh.triangles(), mesh.face_ids.to_vec(), &mesh.lines);
+    }
+
+    /// Give object `id` a point cloud.
+    pub fn upload_points(&mut self, id: u32, points: &[PointVertex]) {
+        let geometry = renderer::create_point_geometry(&self.gpu.device, points);
+        self.set_object_resources(id, geometry, points.len() as u32, &[]);
+        let positions = points.iter().map(|point| point.position).collect();
+        self.state.set_object_points(id, positions, points.iter().map(|point| point.size).collect());
+    }
+
+    /// Add object `id` to the scene, or swap in new geometry and polylines if
+    /// it already exists (keeping its transform, visibility and transparency),
+    /// along with the matching picking geometry.
+    fn set_object_geometry(
+        &mut self,
+        id: u32,
+        geometry: ModelGeometry,
+        num_indices: u32,
+        triangles: Vec<[[f32; 3]; 3]>,
+        face_ids: Vec<u32>,
+        lines: &[Polyline],
+    ) {
+        self.set_object_resources(id, geometry, num_indices, lines);
+        // Update picking geometry and bounds, dropping any selection on this object
+        self.state.set_object_geometry(id, triangles, face_ids, lines.to_vec());
+    }
+
+    fn set_object_resources(&mut self, id: u32, geometry: ModelGeometry, num_indices: u32, lines: &[Polyline]) {
+        let lines = renderer::create_line_geometry(&self.gpu.device, lines);
+        let (uniform_buffer, bind_group) = renderer::create_object_uniforms(&self.gpu.device, &self.gpu.uniform_layout);
+        self.model.set_object(ObjectResources {
+            id,
+            geometry,
+            num_indices,
+            lines,
+            uniform_buffer,
+            bind_group,
+            depth_sort: None,
+        });
+    }
+}
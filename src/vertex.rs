@@ -24,6 +24,19 @@ impl Vertex {
     }
 }
 
+/// A vertex shared between faces in shared-vertex meshes.
+///
+/// Not bound as a vertex buffer: `vs_shared` pulls it from a storage buffer
+/// through the index buffer and looks up the face id per triangle, so the
+/// layout must match `SharedVertex` in shader.wgsl.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct SharedVertex {
+    pub position: [f32; 3], // offset  0, 12 bytes
+    pub normal:   [f32; 3], // offset 12, 12 bytes
+    pub color:    [f32; 4], // offset 24, 16 bytes  (r, g, b, a)
+}
+
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct WireframeVertex {
@@ -45,3 +58,97 @@ impl WireframeVertex {
         }
     }
 }
+
+/// One point of a point cloud, drawn as a round splat.
+///
+/// Bound per instance: `vs_points` expands each point into a quad, taking
+/// the corner from the vertex index.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct PointVertex {
+    pub position: [f32; 3], // offset  0, 12 bytes
+    pub size:     f32,      // offset 12,  4 bytes  (multiplies the point size)
+    pub color:    [f32; 4], // offset 16, 16 bytes  (r, g, b, a)
+}
+
+impl PointVertex {
+    pub fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<PointVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &[
+                wgpu::VertexAttribute { offset: 0,  shader_location: 0, format: wgpu::VertexFormat::Float32x3 },
+                wgpu::VertexAttribute { offset: 12, shader_location: 1, format: wgpu::VertexFormat::Float32 },
+                wgpu::VertexAttribute { offset: 16, shader_location: 2, format: wgpu::VertexFormat::Float32x4 },
+            ],
+        }
+    }
+}
+
+/// One segment of a polyline, drawn as a screen-space quad.
+///
+/// Bound per instance like `PointVertex`. The neighboring points let
+/// `fs_lines` draw joins where segments meet; their `w` is 1 for a join and 0
+/// where the polyline ends and gets a cap instead.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct LineSegment {
+    pub start:       [f32; 3], // offset  0, 12 bytes
+    pub width:       f32,      // offset 12,  4 bytes  (CSS pixels)
+    pub end:         [f32; 3], // offset 16, 12 bytes
+    pub line_id:     u32,      // offset 28,  4 bytes
+    pub prev:        [f32; 4], // offset 32, 16 bytes  (point before `start`, w: join flag)
+    pub next:        [f32; 4], // offset 48, 16 bytes  (point after `end`, w: join flag)
+    pub start_color: [f32; 4], // offset 64, 16 bytes
+    pub end_color:   [f32; 4], // offset 80, 16 bytes
+}
+
+impl LineSegment {
+    pub fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<LineSegment>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &[
+                wgpu::VertexAttribute { offset: 0,  shader_location: 0, format: wgpu::VertexFormat::Float32x3 },
+                wgpu::VertexAttribute { offset: 12, shader_location: 1, format: wgpu::VertexFormat::Float32 },
+                wgpu::VertexAttribute { offset: 16, shader_location: 2, format: wgpu::VertexFormat::Float32x3 },
+                wgpu::VertexAttribute { offset: 28, shader_location: 3, format: wgpu::VertexFormat::Uint32 },
+                wgpu::VertexAttribute { offset: 32, shader_location: 4, format: wgpu::VertexFormat::Float32x4 },
+                wgpu::VertexAttribute { offset: 48, shader_location: 5, format: wgpu::VertexFormat::Float32x4 },
+                wgpu::VertexAttribute { offset: 64, shader_location: 6, format: wgpu::VertexFormat::Float32x4 },
+                wgpu::VertexAttribute { offset: 80, shader_location: 7, format: wgpu::VertexFormat::Float32x4 },
+            ],
+        }
+    }
+}
+
+/// One character of a label, drawn from the glyph atlas as a screen-aligned
+/// quad by `vs_glyphs` in text.wgsl.
+///
+/// Bound per instance. The quad keeps its size on screen wherever its
+/// scene-space anchor projects to.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct GlyphInstance {
+    pub anchor: [f32; 3], // offset  0, 12 bytes
+    pub glyph:  u32,      // offset 12,  4 bytes  (atlas cell)
+    pub offset: [f32; 2], // offset 16,  8 bytes  (lower-left corner from the anchor, CSS pixels, y up)
+    pub size:   [f32; 2], // offset 24,  8 bytes  (CSS pixels)
+    pub color:  [f32; 4], // offset 32, 16 bytes
+}
+
+impl GlyphInstance {
+    pub fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<GlyphInstance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &[
+                wgpu::VertexAttribute { offset: 0,  shader_location: 0, format: wgpu::VertexFormat::Float32x3 },
+                wgpu::VertexAttribute { offset: 12, shader_location: 1, format: wgpu::VertexFormat::Uint32 },
+                wgpu::VertexAttribute { offset: 16, shader_location: 2, format: wgpu::VertexFormat::Float32x2 },
+                wgpu::VertexAttribute { offset: 24, shader_location: 3, format: wgpu::VertexFormat::Float32x2 },
+                wgpu::VertexAttribute { offset: 32, shader_location: 4, format: wgpu::VertexFormat::Float32x4 },
+            ],
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use vibeplot::HeadlessRenderer;
+
+const SIZE: u32 = 64;
+
+/// A model of axis-aligned squares facing +Z, each `(z, half_size, [r, g, b, a])`.
+fn squares(squares: &[(f32, f32, [f32; 4])]) -> String {
+    let mut text = String::new();
+    for (i, &(z, s, [r, g, b, a])) in squares.iter().enumerate() {
+        for (x, y) in [(-s, -s), (s, -s), (s, s), (-s, s)] {
+            text += &format!("vertex {} {} {} 0 0 1 {} {} {} {}\n", x, y, z, r, g, b, a);
+        }
+        let base = i * 4;
+        text += &format!("face {} {} {}\nface {} {} {}\n", base, base + 1, base + 2, base, base + 2, base + 3);
+    }
+    text
+}
+
+fn center_pixel(image: &[u8]) -> [u8; 4] {
+    let at = ((SIZE / 2 * SIZE + SIZE / 2) * 4) as usize;
+    [image[at], image[at + 1], image[at + 2], image[at + 3]]
+}
+
+#[test]
+fn fitted_model_keeps_its_near_face() {
+    let mut renderer = HeadlessRenderer::new(SIZE, SIZE).unwrap();
+    // A small red square in front of a large green one, right at the front
+    // of the fitted clip range
+    renderer.load_model(&squares(&[(-1.0, 1.0, [0.0, 1.0, 0.0, 1.0]), (1.0, 0.3, [1.0, 0.0, 0.0, 1.0])])).unwrap();
+    renderer.set_view("front").unwrap();
+    let [r, g, b, _] = center_pixel(&renderer.render().unwrap());
+    assert!(r > 100 && g < 50 && b < 50, "near face missing: center pixel is {:?}", [r, g, b]);
+}
+
+#[test]
+fn size_beyond_the_device_limit_is_an_error() {
+    let mut renderer = HeadlessRenderer::new(SIZE, SIZE).unwrap();
+    assert!(renderer.resize(u32::MAX, SIZE).is_err());
+    assert!(renderer.resize(0, SIZE).is_err());
+    assert_eq!(renderer.size(), (SIZE, SIZE));
+    assert!(HeadlessRenderer::new(SIZE, u32::MAX).is_err());
+}
+
+#[test]
+fn weighted_transparency_blends_overlapping_quads() {
+    let mut renderer = HeadlessRenderer::new(SIZE, SIZE).unwrap();
+    // Half-transparent red in front of half-transparent blue, both lit head on
+    renderer.load_model(&squares(&[(-0.5, 1.0, [0.0, 0.0, 1.0, 0.5]), (0.5, 1.0, [1.0, 0.0, 0.0, 0.5])])).unwrap();
+    renderer.set_view("front").unwrap();
+    renderer.set_transparent_mode(true);
+    renderer.set_transparent_background(true);
+    renderer.set_transparency_method("weighted").unwrap();
+
+    // Coverage is 1 - 0.5 * 0.5 whatever the order. The quads are close in
+    // depth, so weighted blending gives them near equal shares (188 is sRGB
+    // for linear 0.5), where sorted blending would favor the front one
+    let pixel = center_pixel(&renderer.render().unwrap());
+    let expected = [188, 0, 188, 191];
+    assert!(
+        pixel.iter().zip(expected).all(|(&p, e)| p.abs_diff(e) <= 6),
+        "center pixel is {:?}, expected about {:?}",
+        pixel,
+        expected
+    );
+}
+
+#[test]
+fn axes_around_a_single_point_render() {
+    let mut renderer = HeadlessRenderer::new(SIZE, SIZE).unwrap();
+    renderer.load_points(&[100.0, 0.0, 0.0], &[], None).unwrap();
+    renderer.set_axes(true, "ground", true, true).unwrap();
+    renderer.render().unwrap();
+}
+
+#[test]
+fn polyline_through_the_camera_keeps_its_visible_part() {
+    let mut renderer = HeadlessRenderer::new(SIZE, SIZE).unwrap();
+    // Zoomed in far enough that the line's near end is behind the camera
+    renderer.load_polylines(&[0.3, -0.2, -1.0, 0.3, -0.2, 1.0], &[], &[], 6.0).unwrap();
+    renderer.set_view("front").unwrap();
+    renderer.set_zoom(5.0);
+    let image = renderer.render().unwrap();
+    let lit = image.chunks(4).filter(|p| p[0] > 150 && p[1] > 150 && p[2] > 150).count();
+    assert!(lit > 0, "the line is gone");
+}